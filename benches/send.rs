@@ -0,0 +1,214 @@
+//! Measures the cost of handing payloads to the writer task of a node, and compares commands on
+//! many guilds against the old design, where every command locked the whole node to write to the socket.
+//!
+//! Run with `cargo bench --bench send`, it doesn't need a server.
+
+use dashmap::DashMap;
+use std::{collections::HashMap, sync::Arc, time::{Duration, Instant}};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt, DuplexStream},
+    sync::{mpsc, RwLock}
+};
+use tokio_tungstenite::tungstenite::Message;
+
+const PAYLOADS: u32 = 1_000_000;
+
+const GUILDS: u64 = 500;
+const COMMANDS_PER_GUILD: u64 = 200;
+const UPDATES_PER_GUILD: u64 = 20;
+
+/// A typical `play` payload, the largest op sent on every track.
+fn payload() -> Message {
+    let track = "QAAAjQIAJVJpY2sgQXN0bGV5IC0gTmV2ZXIgR29ubmEgR2l2ZSBZb3UgVXAADlJpY2tBc3RsZXlWRVZPAAAAAAADPCAAC2RRdzR3OVdnWGNRAAEAK2h0dHBzOi8vd3d3LnlvdXR1YmUuY29tL3dhdGNoP3Y9ZFF3NHc5V2dYY1EAB3lvdXR1YmUAAAAAAAAAAA==";
+
+    Message::Text(format!(r#"{{"op":"play","guildId":"123456789012345678","track":"{}","noReplace":false,"startTime":0}}"#, track))
+}
+
+fn report(name: &str, elapsed: Duration) {
+    println!("{:<24} {:>10.1} ns/payload  ({:?} total)", name, elapsed.as_nanos() as f64 / PAYLOADS as f64, elapsed);
+}
+
+fn command(guild_id: u64, volume: u64) -> String {
+    format!(r#"{{"op":"volume","guildId":"{}","volume":{}}}"#, guild_id, volume)
+}
+
+/// An in memory socket, the other end is drained by a task like the server would.
+fn socket() -> DuplexStream {
+    let (socket, mut server) = tokio::io::duplex(64 * 1024);
+
+    tokio::spawn(async move {
+        let mut buf = vec![0; 64 * 1024];
+        while matches!(server.read(&mut buf).await, Ok(read) if read > 0) {}
+    });
+
+    socket
+}
+
+/// The old node, players and the socket behind the same lock.
+struct LockedNode {
+    players: HashMap<u64, u64>,
+    socket: DuplexStream
+}
+
+/// Every command takes the write lock and holds it while the payload is written to the socket,
+/// the read loop takes it too on every player update.
+async fn locked_node() -> (Duration, Duration) {
+    let node = Arc::new(RwLock::new(LockedNode {
+        players: (0..GUILDS).map(|guild_id| (guild_id, 0)).collect(),
+        socket: socket()
+    }));
+
+    let start = Instant::now();
+
+    let updates = {
+        let node = Arc::clone(&node);
+        tokio::spawn(async move {
+            for round in 0..UPDATES_PER_GUILD {
+                for guild_id in 0..GUILDS {
+                    *node.write().await.players.get_mut(&guild_id).unwrap() = round;
+                }
+                tokio::task::yield_now().await;
+            }
+        })
+    };
+
+    let callers = (0..GUILDS).map(|guild_id| {
+        let node = Arc::clone(&node);
+        tokio::spawn(async move {
+            for volume in 0..COMMANDS_PER_GUILD {
+                let mut node = node.write().await;
+                node.players.insert(guild_id, volume);
+                node.socket.write_all(command(guild_id, volume).as_bytes()).await.unwrap();
+            }
+        })
+    }).collect::<Vec<_>>();
+
+    for caller in callers {
+        caller.await.unwrap();
+    }
+
+    let issued = start.elapsed();
+
+    updates.await.unwrap();
+    node.write().await.socket.flush().await.unwrap();
+
+    (issued, start.elapsed())
+}
+
+/// The current node, players in a `DashMap` and the socket owned by a writer task.
+async fn writer_task() -> (Duration, Duration) {
+    let players = Arc::new((0..GUILDS).map(|guild_id| (guild_id, 0)).collect::<DashMap<u64, u64>>());
+    let (sender, mut receiver) = mpsc::unbounded_channel::<Message>();
+    let mut socket = socket();
+
+    let start = Instant::now();
+
+    let writer = tokio::spawn(async move {
+        while let Some(Message::Text(text)) = receiver.recv().await {
+            socket.write_all(text.as_bytes()).await.unwrap();
+        }
+        socket.flush().await.unwrap();
+    });
+
+    let updates = {
+        let players = Arc::clone(&players);
+        tokio::spawn(async move {
+            for round in 0..UPDATES_PER_GUILD {
+                for guild_id in 0..GUILDS {
+                    *players.get_mut(&guild_id).unwrap() = round;
+                }
+                tokio::task::yield_now().await;
+            }
+        })
+    };
+
+    let callers = (0..GUILDS).map(|guild_id| {
+        let players = Arc::clone(&players);
+        let sender = sender.clone();
+        tokio::spawn(async move {
+            for volume in 0..COMMANDS_PER_GUILD {
+                players.insert(guild_id, volume);
+                sender.send(Message::Text(command(guild_id, volume))).unwrap();
+            }
+        })
+    }).collect::<Vec<_>>();
+
+    for caller in callers {
+        caller.await.unwrap();
+    }
+
+    let issued = start.elapsed();
+
+    drop(sender);
+    updates.await.unwrap();
+    writer.await.unwrap();
+
+    (issued, start.elapsed())
+}
+
+fn report_guilds(name: &str, (issued, written): (Duration, Duration)) {
+    let commands = (GUILDS * COMMANDS_PER_GUILD) as f64;
+
+    println!(
+        "{:<24} {:>10.1} ns/command issued, {:>10.1} ns/command written  ({} guilds)",
+        name,
+        issued.as_nanos() as f64 / commands,
+        written.as_nanos() as f64 / commands,
+        GUILDS
+    );
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let message = payload();
+
+    // Hand off only, the receiver drains everything afterwards, like a writer falling behind
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let start = Instant::now();
+
+    for _ in 0..PAYLOADS {
+        sender.send(message.clone()).unwrap();
+    }
+
+    report("unbounded send", start.elapsed());
+
+    drop(sender);
+
+    runtime.block_on(async {
+        let start = Instant::now();
+        let mut received = 0u32;
+
+        while receiver.recv().await.is_some() {
+            received += 1;
+        }
+
+        assert_eq!(received, PAYLOADS);
+        report("unbounded drain", start.elapsed());
+    });
+
+    // The same payloads through a bounded channel, awaiting room for each one
+    runtime.block_on(async {
+        let (sender, mut receiver) = mpsc::channel(64);
+
+        let reader = tokio::spawn(async move {
+            while receiver.recv().await.is_some() {}
+        });
+
+        let start = Instant::now();
+
+        for _ in 0..PAYLOADS {
+            sender.send(message.clone()).await.unwrap();
+        }
+
+        drop(sender);
+        reader.await.unwrap();
+
+        report("bounded (64) send", start.elapsed());
+    });
+
+    // Commands on many guilds at once, with player updates coming from the read loop
+    let runtime = tokio::runtime::Builder::new_multi_thread().build().unwrap();
+
+    report_guilds("whole node lock", runtime.block_on(locked_node()));
+    report_guilds("writer task", runtime.block_on(writer_task()));
+}
@@ -0,0 +1,17 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use crate::{node::UniversalNode, model::track::{Track, QueuedTrack}};
+
+/// Chooses what to play once the queue of a player runs dry.
+///
+/// The returned track is queued automatically with [`TrackRequester::autoplay`] as requester.
+///
+/// [`TrackRequester::autoplay`]: crate::model::track::TrackRequester::autoplay
+#[async_trait]
+pub trait AutoplayProvider: Send + Sync + 'static {
+    /// Returns the track to play after the given one, which was the last of the queue.
+    /// `None` leaves the player stopped.
+    ///
+    /// Related tracks can be searched with [`UniversalNode::get_tracks`].
+    async fn next_track(&self, node: Arc<UniversalNode>, last: QueuedTrack) -> Option<Track>;
+}
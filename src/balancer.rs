@@ -0,0 +1,180 @@
+use async_trait::async_trait;
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering}
+};
+use rand::Rng;
+use crate::{
+    node::UniversalNode,
+    model::gateway::Stats
+};
+
+/// Strategy used by the cluster to choose the node new players are created on.
+#[async_trait]
+pub trait LoadBalancer: Send + Sync + 'static {
+    /// Selects one of the given nodes, returns `None` if none of them can be used.
+    async fn select(&self, nodes: &[Arc<UniversalNode>]) -> Option<Arc<UniversalNode>>;
+}
+
+/// Selects the node with fewer players.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LeastPlayers;
+
+#[async_trait]
+impl LoadBalancer for LeastPlayers {
+    async fn select(&self, nodes: &[Arc<UniversalNode>]) -> Option<Arc<UniversalNode>> {
+        let mut best: Option<(usize, &Arc<UniversalNode>)> = None;
+
+        for node in nodes {
+            let players = node.players.len();
+
+            if best.map(|(min, _)| players < min).unwrap_or(true) {
+                best = Some((players, node));
+            }
+        }
+
+        best.map(|(_, node)| Arc::clone(node))
+    }
+}
+
+/// Selects every node in turns.
+#[derive(Debug, Default)]
+pub struct RoundRobin {
+    counter: AtomicUsize
+}
+
+#[async_trait]
+impl LoadBalancer for RoundRobin {
+    async fn select(&self, nodes: &[Arc<UniversalNode>]) -> Option<Arc<UniversalNode>> {
+        if nodes.is_empty() { return None; }
+
+        let index = self.counter.fetch_add(1, Ordering::Relaxed) % nodes.len();
+
+        Some(Arc::clone(&nodes[index]))
+    }
+}
+
+/// Selects a random node.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Random;
+
+#[async_trait]
+impl LoadBalancer for Random {
+    async fn select(&self, nodes: &[Arc<UniversalNode>]) -> Option<Arc<UniversalNode>> {
+        if nodes.is_empty() { return None; }
+
+        let index = rand::thread_rng().gen_range(0..nodes.len());
+
+        Some(Arc::clone(&nodes[index]))
+    }
+}
+
+/// Selects the node with the lowest penalty calculated from its last stats, the same way Lavalink clients do.
+///
+/// Nodes that didn't send any stats yet use their number of players as penalty.
+/// This is the strategy used by default.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Penalty;
+
+#[async_trait]
+impl LoadBalancer for Penalty {
+    async fn select(&self, nodes: &[Arc<UniversalNode>]) -> Option<Arc<UniversalNode>> {
+        let mut best: Option<(f64, &Arc<UniversalNode>)> = None;
+
+        for node in nodes {
+            let total = match &*node.stats.read() {
+                Some(stats) => penalty(stats),
+                None => node.players.len() as f64
+            };
+
+            if best.map(|(min, _)| total < min).unwrap_or(true) {
+                best = Some((total, node));
+            }
+        }
+
+        best.map(|(_, node)| Arc::clone(node))
+    }
+}
+
+/// Calculates the penalty of a node from its stats, the higher the penalty the more loaded the node is.
+pub fn penalty(stats: &Stats) -> f64 {
+    let player_penalty = stats.playing_players as f64;
+
+    let cpu_penalty = 1.05f64.powf(100. * stats.cpu.system_load) * 10. - 10.;
+
+    let (deficit_frame_penalty, null_frame_penalty) = match &stats.frame_stats {
+        Some(frames) => (
+            1.03f64.powf(500. * (frames.deficit as f64 / 3000.)) * 600. - 600.,
+            (1.03f64.powf(500. * (frames.nulled as f64 / 3000.)) * 300. - 300.) * 2.
+        ),
+        None => (0., 0.)
+    };
+
+    player_penalty + cpu_penalty + deficit_frame_penalty + null_frame_penalty
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::gateway::{Cpu, FrameStats, Memory};
+
+    fn stats(playing_players: i64, system_load: f64, frames: Option<(i64, i64)>) -> Stats {
+        Stats {
+            playing_players,
+            op: "stats".to_string(),
+            memory: Memory {
+                reservable: 0,
+                used: 0,
+                free: 0,
+                allocated: 0,
+            },
+            frame_stats: frames.map(|(deficit, nulled)| FrameStats {
+                sent: 3000,
+                deficit,
+                nulled,
+            }),
+            players: playing_players,
+            cpu: Cpu {
+                cores: 4,
+                system_load,
+                lavalink_load: 0.,
+            },
+            uptime: 0,
+        }
+    }
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn idle_node_has_no_penalty() {
+        assert!(approx(penalty(&stats(0, 0., None)), 0.));
+        assert!(approx(penalty(&stats(0, 0., Some((0, 0)))), 0.));
+    }
+
+    #[test]
+    fn playing_players_add_one_each() {
+        assert!(approx(penalty(&stats(7, 0., None)), 7.));
+    }
+
+    #[test]
+    fn cpu_load_grows_exponentially() {
+        let low = penalty(&stats(0, 0.1, None));
+        let high = penalty(&stats(0, 0.5, None));
+
+        assert!(approx(high, 1.05f64.powf(50.) * 10. - 10.));
+        assert!(high > low * 4.);
+    }
+
+    #[test]
+    fn lost_frames_weigh_more_than_players() {
+        let busy = penalty(&stats(50, 0., None));
+        let deficit = penalty(&stats(0, 0., Some((300, 0))));
+        let nulled = penalty(&stats(0, 0., Some((0, 300))));
+
+        assert!(deficit > busy);
+        assert!(nulled > busy);
+        assert!(approx(nulled, (1.03f64.powf(50.) * 300. - 300.) * 2.));
+    }
+}
@@ -1,7 +1,8 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 use crate::{
     cluster::Cluster,
-    node::UniversalNode
+    node::UniversalNode,
+    reconnect::ReconnectPolicy
 };
 
 pub struct NodeBuilder {
@@ -11,7 +12,13 @@ pub struct NodeBuilder {
     pub(crate) pass: String,
     pub(crate) shards: u64,
     pub(crate) id: Option<u64>,
-    pub(crate) node_id: Option<u8>
+    pub(crate) node_id: Option<u8>,
+    pub(crate) resume_key: Option<String>,
+    pub(crate) resume_timeout: u64,
+    pub(crate) regions: Vec<String>,
+    pub(crate) reconnect_policy: Option<ReconnectPolicy>,
+    pub(crate) outbox_size: usize,
+    pub(crate) outbox_expiry: Duration
 }
 
 impl Default for NodeBuilder {
@@ -23,7 +30,13 @@ impl Default for NodeBuilder {
             pass: "youshallnotpass".to_string(),
             shards: 1,
             id: None,
-            node_id: None
+            node_id: None,
+            resume_key: None,
+            resume_timeout: 60,
+            regions: Vec::new(),
+            reconnect_policy: None,
+            outbox_size: 0,
+            outbox_expiry: Duration::from_secs(30)
         }
     }
 }
@@ -59,6 +72,51 @@ impl NodeBuilder {
         self
     }
 
+    /// Sets the key used to resume the session if the websocket connection is lost.
+    /// If no key is set, every player on the server is destroyed when the connection drops.
+    pub fn set_resume_key(&mut self, key: impl ToString) -> &mut Self {
+        self.resume_key = Some(key.to_string());
+        self
+    }
+
+    /// Sets the time the server will keep the session alive waiting for a resume, defaults to 60 seconds.
+    pub fn set_resume_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.resume_timeout = timeout.as_secs();
+        self
+    }
+
+    /// Adds a region tag to the node, it can be either a broad region such as `europe` or `us`,
+    /// or a Discord voice server name such as `rotterdam`. See [`crate::region`] for the known names.
+    pub fn add_region(&mut self, region: impl ToString) -> &mut Self {
+        self.regions.push(region.to_string().to_lowercase());
+        self
+    }
+
+    /// Sets all region tags of the node, replacing the existing ones.
+    pub fn set_regions<I, R>(&mut self, regions: I) -> &mut Self
+    where
+        I: IntoIterator<Item = R>,
+        R: ToString
+    {
+        self.regions = regions.into_iter().map(|r| r.to_string().to_lowercase()).collect();
+        self
+    }
+
+    /// Sets the reconnect policy of this node, overriding the one of the cluster.
+    pub fn set_reconnect_policy(&mut self, policy: ReconnectPolicy) -> &mut Self {
+        self.reconnect_policy = Some(policy);
+        self
+    }
+
+    /// Enables buffering of payloads sent while the node is reconnecting, up to `max_size` payloads.
+    /// Payloads older than `expiry` are discarded, and the buffer is only flushed if the session is resumed,
+    /// otherwise the players the payloads were meant for no longer exist on the server, see [`NodeBuilder::set_resume_key`].
+    pub fn set_outbox(&mut self, max_size: usize, expiry: Duration) -> &mut Self {
+        self.outbox_size = max_size;
+        self.outbox_expiry = expiry;
+        self
+    }
+
     pub(crate) fn build(mut self, cluster: Arc<Cluster>, node_id: u8) -> Arc<UniversalNode> {
         self.node_id = Some(node_id);
 
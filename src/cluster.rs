@@ -2,17 +2,29 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use parking_lot::Mutex;
 use typemap_rev::TypeMap;
-use crate::{builder::NodeBuilder, events::EventHandler, node::UniversalNode};
+use crate::{builder::NodeBuilder, events::EventHandler, node::UniversalNode, balancer::{LoadBalancer, Penalty}, reconnect::ReconnectPolicy, store::{QueueStore, PlayerSnapshot}, presets::PresetRegistry, model::events::Filters};
 use dashmap::DashMap;
 use std::sync::atomic::{AtomicU8, Ordering};
-use std::collections::HashMap;
 use crate::error::{ClusterResult, ClusterError};
+use crate::events::{process, EventType};
+use crate::model::library_events::{PlayerMigrated, PlayerDropped};
+use tracing::{info, warn, error};
+use songbird::ConnectionInfo;
+use serenity::model::event::VoiceServerUpdateEvent;
 
 #[non_exhaustive]
 pub struct Cluster {
     pub event_handler: Arc<dyn EventHandler>,
+    pub load_balancer: Arc<dyn LoadBalancer>,
     pub nodes: DashMap<u8, Arc<UniversalNode>>,
-    pub reconnect_attempts: u8,
+    /// Every node managed by the cluster, including the ones that are currently reconnecting
+    pub(crate) registry: DashMap<u8, Arc<UniversalNode>>,
+    pub reconnect_policy: ReconnectPolicy,
+    pub queue_store: Option<Arc<dyn QueueStore>>,
+    /// Filter presets that can be applied to players
+    pub presets: PresetRegistry,
+    /// Restored players waiting for a session to be created on their guild
+    pub(crate) pending_restores: DashMap<u64, PlayerSnapshot>,
     pub shared_data: Arc<RwLock<TypeMap>>,
     pub node_counter: AtomicU8,
     pub self_ref: Mutex<Option<Arc<Self>>>
@@ -25,8 +37,13 @@ impl Cluster {
     fn new(builder: ClusterBuilder) -> ClusterResult<Arc<Self>> {
         let cluster = Arc::new(Self {
             event_handler: builder.event_handler,
+            load_balancer: builder.load_balancer,
             nodes: DashMap::new(),
-            reconnect_attempts: builder.reconnect_attempts,
+            registry: DashMap::new(),
+            reconnect_policy: builder.reconnect_policy,
+            queue_store: builder.queue_store,
+            presets: builder.presets,
+            pending_restores: DashMap::new(),
             shared_data: Arc::new(RwLock::new(builder.data)),
             node_counter: AtomicU8::new(0),
             self_ref: Mutex::new(None)
@@ -47,57 +64,200 @@ impl Cluster {
     }
 
     pub async fn get_best(&self) -> ClusterResult<Arc<UniversalNode>>{
-        //get the best node based on number of player every node has, the node with fewer players gets returned
+        //get the best node using the configured load balancer
+        let nodes = self.available_nodes();
 
-        if self.nodes.len() == 1 {
-            let node = self.nodes.iter().next().map(|item| Arc::clone(&item.value()));
-            
-            if node.is_none() { return Err(ClusterError::CannotFindNode) }
+        if nodes.is_empty() { return Err(ClusterError::CannotFindBestNode); }
 
-            return Ok(node.unwrap())
+        if nodes.len() == 1 {
+            return Ok(Arc::clone(&nodes[0]));
         }
 
-        let mut counts: HashMap<u8, u32> = HashMap::new();
-        let mut min: Option<(u8, u32)> = None;
-        for item in self.nodes.iter() {
-            counts.insert(item.key().clone(), item.value().read().await.players.len() as u32);
+        match self.load_balancer.select(&nodes).await {
+            Some(node) => Ok(node),
+            None => Err(ClusterError::CannotFindBestNode)
         }
+    }
 
-        for (id, i) in counts.iter() {
-            if min.is_none() { min = Some((id.clone(), i.clone())) }
-            else {
-                if let Some((_, r)) = min {
-                    if i < &r {
-                        min = Some((id.clone(), i.clone()));
-                    }
-                }
-            }
+    /// Gets the best node tagged with the given region, if there are none, the best node of the whole cluster is returned.
+    pub async fn get_best_for_region(&self, region: impl ToString) -> ClusterResult<Arc<UniversalNode>> {
+        self.get_best_matching(&[region.to_string().to_lowercase()]).await
+    }
+
+    /// Gets the best node located on the region of the given Discord voice endpoint,
+    /// for example `rotterdam1234.discord.media` prefers nodes tagged as `rotterdam` or `europe`.
+    ///
+    /// If there are no nodes on that region, the best node of the whole cluster is returned.
+    pub async fn get_best_for_endpoint(&self, endpoint: &str) -> ClusterResult<Arc<UniversalNode>> {
+        self.get_best_matching(&crate::region::endpoint_regions(endpoint)).await
+    }
+
+    /// Gets the best node for the voice server of the given connection, see [`Cluster::get_best_for_endpoint`].
+    pub async fn get_best_for_connection(&self, conn_info: &ConnectionInfo) -> ClusterResult<Arc<UniversalNode>> {
+        self.get_best_for_endpoint(&conn_info.endpoint).await
+    }
+
+    /// Gets the best node for the voice server of the given event, see [`Cluster::get_best_for_endpoint`].
+    pub async fn get_best_for_voice_server(&self, event: &VoiceServerUpdateEvent) -> ClusterResult<Arc<UniversalNode>> {
+        match &event.endpoint {
+            Some(endpoint) => self.get_best_for_endpoint(endpoint).await,
+            None => self.get_best().await
         }
+    }
 
-        if min.is_none() { return Err(ClusterError::CannotFindBestNode); }
+    async fn get_best_matching(&self, regions: &[String]) -> ClusterResult<Arc<UniversalNode>> {
+        let mut candidates = Vec::new();
 
-        let nodes = &self.nodes;
-        let best_node = nodes.get(&min.unwrap().0);
+        for node in self.available_nodes() {
+            let matches = node.regions.iter().any(|r| regions.contains(r));
 
-        if best_node.is_none() { return Err(ClusterError::CannotFindBestNode); }
+            if matches {
+                candidates.push(node);
+            }
+        }
 
-        let node = Arc::clone(&best_node.unwrap());
+        let selected = match candidates.len() {
+            0 => None,
+            1 => candidates.pop(),
+            _ => self.load_balancer.select(&candidates).await
+        };
 
-        Ok(node)
+        match selected {
+            Some(node) => Ok(node),
+            None => self.get_best().await
+        }
     }
 
     pub async fn get_player_node(&self, guild: impl Into<u64>) -> ClusterResult<Arc<UniversalNode>> {
         //get the node iterating over cluster's nodes and checking if player loops contains a certain guild id
         let guild = guild.into();
-        for iter in self.nodes.iter() {
-            if iter.value().read().await.players.contains_key(&guild) {
-                return Ok(Arc::clone(iter.value()))
+        for node in self.available_nodes() {
+            if node.players.contains_key(&guild) {
+                return Ok(node)
             }
         }
 
         Err(ClusterError::CannotFindNode)
     }
 
+    /// Returns the nodes currently connected, sorted by their id.
+    fn available_nodes(&self) -> Vec<Arc<UniversalNode>> {
+        let mut nodes = self.nodes.iter()
+            .map(|item| (*item.key(), Arc::clone(item.value())))
+            .collect::<Vec<_>>();
+
+        nodes.sort_by_key(|(id, _)| *id);
+
+        nodes.into_iter().map(|(_, node)| node).collect()
+    }
+
+    /// Moves every player of the given node to the best available node, recreating its state there.
+    /// Players that can't be moved to any node are lost.
+    pub(crate) async fn migrate_players(&self, from: Arc<UniversalNode>) {
+        let from_id = from.node_id;
+
+        // The fades of the old node would keep changing the volume of players it doesn't hold anymore
+        from.cancel_fades();
+
+        let players = {
+            let guilds = from.players.iter().map(|item| *item.key()).collect::<Vec<_>>();
+
+            guilds.into_iter().filter_map(|guild_id| from.players.remove(&guild_id)).collect::<Vec<_>>()
+        };
+
+        for (guild_id, player) in players {
+            let node = match self.get_best().await {
+                Ok(node) => node,
+                Err(why) => {
+                    warn!("No node available to migrate player of guild id {} from node id {}, dropping it", guild_id, from_id);
+
+                    self.player_dropped(Arc::clone(&from), guild_id, why.to_string());
+                    continue;
+                }
+            };
+
+            let to_id = node.node_id;
+
+            if let Err(why) = node.restore_player(guild_id, player) {
+                error!("Error migrating player of guild id {} to node id {}, error: {}", guild_id, to_id, why.to_string());
+
+                self.player_dropped(Arc::clone(&from), guild_id, why.to_string());
+                continue;
+            }
+
+            info!("Migrated player of guild id {} from node id {} to node id {}", guild_id, from_id, to_id);
+
+            let event = PlayerMigrated {
+                guild_id,
+                from_node: from_id,
+                to_node: to_id,
+            };
+
+            process(node, Arc::clone(&self.event_handler), EventType::PlayerMigrated(event));
+        }
+    }
+
+    /// Tells the event handler that a player was lost, so a new session can be created for its guild.
+    fn player_dropped(&self, node: Arc<UniversalNode>, guild_id: u64, reason: String) {
+        let event = PlayerDropped {
+            guild_id,
+            reason,
+        };
+
+        process(node, Arc::clone(&self.event_handler), EventType::PlayerDropped(event));
+    }
+
+    /// Saves the players of every node on the queue store, replacing the ones saved before.
+    pub async fn save_players(&self) -> ClusterResult<()> {
+        let store = match &self.queue_store {
+            Some(store) => Arc::clone(store),
+            None => return Err(ClusterError::MissingQueueStore)
+        };
+
+        let mut snapshots = Vec::new();
+
+        for node in self.registry.iter() {
+            for player in node.players.iter() {
+                snapshots.push(PlayerSnapshot::from_player(*player.key(), player.value()));
+            }
+        }
+
+        // Keep the restored players that didn't get a session yet
+        for pending in self.pending_restores.iter() {
+            snapshots.push(pending.value().clone());
+        }
+
+        let count = snapshots.len();
+
+        store.save(snapshots).await?;
+
+        info!("Saved {} players on the queue store", count);
+
+        Ok(())
+    }
+
+    /// Loads the players saved on the queue store, each one is recreated as soon as
+    /// a session is created for its guild.
+    ///
+    /// Returns the ids of the guilds waiting for a session, so their voice connections can be restored.
+    pub async fn restore_players(&self) -> ClusterResult<Vec<u64>> {
+        let store = match &self.queue_store {
+            Some(store) => Arc::clone(store),
+            None => return Err(ClusterError::MissingQueueStore)
+        };
+
+        let snapshots = store.load().await?;
+        let guilds = snapshots.iter().map(|snapshot| snapshot.guild_id).collect::<Vec<_>>();
+
+        for snapshot in snapshots {
+            self.pending_restores.insert(snapshot.guild_id, snapshot);
+        }
+
+        info!("Loaded {} players from the queue store", guilds.len());
+
+        Ok(guilds)
+    }
+
     fn get_id(&self) -> u8 {
         let mut count = self.node_counter.fetch_add(1, Ordering::Relaxed);
         count += 1;
@@ -126,10 +286,81 @@ impl Cluster {
 
         let node = builder.build(cluster, id);
 
+        self.registry.insert(id, Arc::clone(&node));
+
         UniversalNode::run(node);
 
         Ok(())
     }
+
+    /// Removes a node from cluster, its players are either moved to other nodes or destroyed
+    /// depending on the given mode, then the connection is closed and the node task stopped.
+    pub async fn remove_node(&self, id: u8, mode: DrainMode) -> ClusterResult<()> {
+        let node = match self.registry.remove(&id) {
+            Some((_, node)) => node,
+            None => return Err(ClusterError::CannotFindNode)
+        };
+
+        // Mark it before removing it so a reconnection doesn't add it back,
+        // and remove it first so the node doesn't get chosen for migrated players
+        node.mark_shutting_down();
+
+        self.nodes.remove(&id);
+
+        if node.is_connected() {
+            if let Err(why) = node.destroy_server_players() {
+                warn!("Failed to destroy players of node id {} on the server, error: {}", id, why.to_string());
+            }
+        }
+
+        if let DrainMode::Destroy = mode {
+            node.cancel_fades();
+            node.players.clear();
+        }
+
+        if let DrainMode::Migrate = mode {
+            self.migrate_players(Arc::clone(&node)).await;
+        }
+
+        node.shutdown().await;
+
+        info!("Node id {} removed from cluster successfully", id);
+
+        process(node, Arc::clone(&self.event_handler), EventType::NodeRemoved);
+
+        Ok(())
+    }
+
+    /// Shuts down the whole cluster, destroying every player and stopping all nodes.
+    ///
+    /// If a queue store is configured, the players are saved before being destroyed.
+    pub async fn shutdown(&self) {
+        if self.queue_store.is_some() {
+            if let Err(why) = self.save_players().await {
+                error!("Failed to save players while shutting down, error: {}", why.to_string());
+            }
+        }
+
+        let ids = self.registry.iter().map(|item| *item.key()).collect::<Vec<_>>();
+
+        for id in ids {
+            if let Err(why) = self.remove_node(id, DrainMode::Destroy).await {
+                warn!("Failed to remove node id {} while shutting down, error: {}", id, why.to_string());
+            }
+        }
+
+        // Break the reference cycle so the cluster can be dropped
+        *self.self_ref.lock() = None;
+    }
+}
+
+/// What to do with the players of a node that is removed from cluster.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DrainMode {
+    /// Move the players to the best available nodes
+    Migrate,
+    /// Destroy the players
+    Destroy
 }
 
 impl typemap_rev::TypeMapKey for Cluster {
@@ -138,18 +369,24 @@ impl typemap_rev::TypeMapKey for Cluster {
 
 pub struct ClusterBuilder {
     pub event_handler: Arc<dyn EventHandler>,
+    pub load_balancer: Arc<dyn LoadBalancer>,
     pub nodes: Vec<NodeBuilder>,
     pub data: TypeMap,
-    pub reconnect_attempts: u8
+    pub reconnect_policy: ReconnectPolicy,
+    pub queue_store: Option<Arc<dyn QueueStore>>,
+    pub presets: PresetRegistry
 }
 
 impl ClusterBuilder {
     pub fn new<H: EventHandler + 'static>(handler: H) -> Self {
         Self {
             event_handler: Arc::new(handler),
+            load_balancer: Arc::new(Penalty),
             nodes: Vec::new(),
             data: TypeMap::new(),
-            reconnect_attempts: 5
+            reconnect_policy: ReconnectPolicy::default(),
+            queue_store: None,
+            presets: PresetRegistry::default()
         }
     }
 
@@ -166,8 +403,38 @@ impl ClusterBuilder {
             self
     }
 
+    /// Sets the maximum number of consecutive failed reconnect attempts of the default reconnect policy.
     pub fn reconnect_attempts(&mut self, attempts: u8) -> &mut Self {
-        self.reconnect_attempts = attempts;
+        self.reconnect_policy.max_attempts = Some(attempts as u32);
+
+        self
+    }
+
+    /// Sets the reconnect policy used by every node that doesn't set its own one.
+    pub fn reconnect_policy(&mut self, policy: ReconnectPolicy) -> &mut Self {
+        self.reconnect_policy = policy;
+
+        self
+    }
+
+    /// Sets the strategy used to choose the node new players are created on, defaults to [`Penalty`].
+    pub fn load_balancer<B: LoadBalancer>(&mut self, balancer: B) -> &mut Self {
+        self.load_balancer = Arc::new(balancer);
+
+        self
+    }
+
+    /// Sets the storage used to save and restore the players, see [`Cluster::save_players`] and [`Cluster::restore_players`].
+    pub fn queue_store<S: QueueStore>(&mut self, store: S) -> &mut Self {
+        self.queue_store = Some(Arc::new(store));
+
+        self
+    }
+
+    /// Registers a filter preset that can be applied to players along with the built-in ones,
+    /// replacing the preset with the same name, if any.
+    pub fn add_preset(&mut self, name: impl ToString, filters: Filters) -> &mut Self {
+        self.presets.register(name, filters);
 
         self
     }
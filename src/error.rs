@@ -5,6 +5,7 @@ use std::{
         Formatter,
         Result,
     },
+    time::Duration,
 };
 use tokio_tungstenite::tungstenite::error::Error as TungsteniteError;
 
@@ -25,6 +26,19 @@ pub enum AndelinkError {
     InvalidDataToVolume,
     InvalidDataToSeek,
     ErrorSendingPayload(TungsteniteError),
+    OutboxFull,
+    QueueIndexOutOfBounds,
+    QueueFull(usize),
+    RequesterQuotaExceeded(usize),
+    TrackTooLong(Duration),
+    StreamsNotAllowed,
+    DuplicateTrack,
+    Store(Box<dyn Error + Send + Sync>),
+    InvalidFilterValue(&'static str),
+    PresetNotFound(String),
+    NothingPlaying,
+    TrackNotSeekable,
+    PositionUnknown,
 }
 
 impl Error for AndelinkError {}
@@ -45,6 +59,19 @@ impl Display for AndelinkError {
             AndelinkError::InvalidDataToVoiceUpdate => write!(f, "Invalid data was provided to the `voiceUpdate` json."),
             AndelinkError::ErrorSendingPayload(why) => write!(f, "Error while sending payload, json => {:?}", why),
             AndelinkError::PlayerNotFound => write!(f, "Player not found"),
+            AndelinkError::OutboxFull => write!(f, "The node is reconnecting and its outbox is full."),
+            AndelinkError::QueueIndexOutOfBounds => write!(f, "The given position is out of the queue bounds."),
+            AndelinkError::QueueFull(max) => write!(f, "The queue is full, it can't have more than {} tracks.", max),
+            AndelinkError::RequesterQuotaExceeded(max) => write!(f, "The requester can't have more than {} tracks on the queue.", max),
+            AndelinkError::TrackTooLong(max) => write!(f, "The track is longer than the maximum allowed of {} seconds.", max.as_secs()),
+            AndelinkError::StreamsNotAllowed => write!(f, "Streams are not allowed on this queue."),
+            AndelinkError::DuplicateTrack => write!(f, "The track is already playing or queued."),
+            AndelinkError::Store(why) => write!(f, "Error while accessing the queue store: {}", why),
+            AndelinkError::InvalidFilterValue(parameter) => write!(f, "The value of the `{}` filter parameter is out of range.", parameter),
+            AndelinkError::PresetNotFound(name) => write!(f, "There is no preset named `{}`.", name),
+            AndelinkError::NothingPlaying => write!(f, "The player is not playing any track."),
+            AndelinkError::TrackNotSeekable => write!(f, "The current track can't be seeked, it may be a stream."),
+            AndelinkError::PositionUnknown => write!(f, "The position of the current track is unknown, it has no info."),
             //_ => write!(f, "Unhandled error occurred."),
         }
     }
@@ -65,6 +92,7 @@ pub enum ClusterError {
     CannotFindBestNode,
     CannotAddNode,
     CannotUpdateVoiceState,
+    MissingQueueStore,
     Tungstenite(TungsteniteError),
     Andelink(AndelinkError)
 }
@@ -80,6 +108,7 @@ impl Display for ClusterError {
             ClusterError::CannotAddNode => write!(f, "Cannot add node to cluster"),
             ClusterError::Tungstenite(e) => write!(f, "{:#?}", e),
             ClusterError::CannotUpdateVoiceState => write!(f, "Failed to update node voice state"),
+            ClusterError::MissingQueueStore => write!(f, "No queue store was configured on the cluster"),
             ClusterError::Andelink(e) => e.fmt(f)
         }
     }
@@ -2,7 +2,7 @@ use async_trait::async_trait;
 
 use crate::{
     node::UniversalNode,
-    model::gateway::*,
+    model::{gateway::*, library_events::*},
 };
 use std::sync::Arc;
 
@@ -16,12 +16,34 @@ pub trait EventHandler: Send + Sync + 'static {
     async fn track_start(&self, _node: Arc<UniversalNode>, _event: TrackStart) {}
     /// Event that triggers when a track finishes playing.
     async fn track_finish(&self, _node: Arc<UniversalNode>, _event: TrackFinish) {}
-    /*///Event triggered when track gets stuck **NOT WORKING**
-    async fn track_stuck(&self, _node: Arc<UniversalNode>) {}
-    ///Event triggered when there is an exception playing the track **NOT WORKING**
-    async fn track_exception(&self, _node: Arc<UniversalNode>) {}*/
+    /// Event triggered when track gets stuck, the queue is advanced automatically.
+    async fn track_stuck(&self, _node: Arc<UniversalNode>, _event: TrackStuck) {}
+    /// Event triggered when there is an exception playing the track.
+    async fn track_exception(&self, _node: Arc<UniversalNode>, _event: TrackException) {}
     ///Event triggered when an audio web socket is disconnected from discord
     async fn socket_closed(&self, _node: Arc<UniversalNode>, _event: WebSocketClosed) {}
+    /// Event triggered when a player is moved to another node because its node was removed from cluster,
+    /// the provided node is the one now holding the player.
+    async fn player_migrated(&self, _node: Arc<UniversalNode>, _event: PlayerMigrated) {}
+    /// Event triggered when a player is lost because it couldn't be recreated on the server,
+    /// a new session needs to be created for its guild.
+    async fn player_dropped(&self, _node: Arc<UniversalNode>, _event: PlayerDropped) {}
+    /// Event triggered when a node connects to the server and is added to cluster.
+    async fn node_connected(&self, _node: Arc<UniversalNode>, _event: NodeConnected) {}
+    /// Event triggered when a node loses its connection and is temporarily removed from cluster.
+    async fn node_disconnected(&self, _node: Arc<UniversalNode>, _event: NodeDisconnected) {}
+    /// Event triggered when a node tries to reconnect to the server.
+    async fn node_reconnecting(&self, _node: Arc<UniversalNode>, _event: NodeReconnecting) {}
+    /// Event triggered when a node is permanently removed from cluster.
+    async fn node_removed(&self, _node: Arc<UniversalNode>) {}
+    /// Event triggered when a track is added to the queue of a player.
+    async fn track_queued(&self, _node: Arc<UniversalNode>, _event: TrackQueued) {}
+    /// Event triggered when the queue of a player moves to the next track, because the previous one
+    /// finished, was skipped, or the player was idle.
+    async fn track_advanced(&self, _node: Arc<UniversalNode>, _event: TrackAdvanced) {}
+    /// Event triggered when the last track of the queue finishes and there is nothing left to play,
+    /// the autoplay of the player, if any, runs after this.
+    async fn queue_finished(&self, _node: Arc<UniversalNode>, _event: QueueFinished) {}
 }
 
 pub(crate) fn process(node: Arc<UniversalNode>, handler: Arc<dyn EventHandler>, event_type: EventType) {
@@ -46,10 +68,65 @@ pub(crate) fn process(node: Arc<UniversalNode>, handler: Arc<dyn EventHandler>,
                 handler.track_finish(node, e).await;
             });
         },
+        EventType::TrackStuck(e) => {
+            tokio::spawn(async move {
+                handler.track_stuck(node, e).await;
+            });
+        },
+        EventType::TrackException(e) => {
+            tokio::spawn(async move {
+                handler.track_exception(node, e).await;
+            });
+        },
         EventType::WebSocketClosed(e) => {
             tokio::spawn(async move {
                 handler.socket_closed(node, e).await;
             });
+        },
+        EventType::PlayerMigrated(e) => {
+            tokio::spawn(async move {
+                handler.player_migrated(node, e).await;
+            });
+        },
+        EventType::PlayerDropped(e) => {
+            tokio::spawn(async move {
+                handler.player_dropped(node, e).await;
+            });
+        },
+        EventType::NodeConnected(e) => {
+            tokio::spawn(async move {
+                handler.node_connected(node, e).await;
+            });
+        },
+        EventType::NodeDisconnected(e) => {
+            tokio::spawn(async move {
+                handler.node_disconnected(node, e).await;
+            });
+        },
+        EventType::NodeReconnecting(e) => {
+            tokio::spawn(async move {
+                handler.node_reconnecting(node, e).await;
+            });
+        },
+        EventType::NodeRemoved => {
+            tokio::spawn(async move {
+                handler.node_removed(node).await;
+            });
+        },
+        EventType::TrackQueued(e) => {
+            tokio::spawn(async move {
+                handler.track_queued(node, e).await;
+            });
+        },
+        EventType::TrackAdvanced(e) => {
+            tokio::spawn(async move {
+                handler.track_advanced(node, e).await;
+            });
+        },
+        EventType::QueueFinished(e) => {
+            tokio::spawn(async move {
+                handler.queue_finished(node, e).await;
+            });
         }
     }
 }
@@ -59,5 +136,16 @@ pub(crate) enum EventType {
     PlayerUpdate(PlayerUpdate),
     TrackStart(TrackStart),
     TrackFinish(TrackFinish),
-    WebSocketClosed(WebSocketClosed)
+    TrackStuck(TrackStuck),
+    TrackException(TrackException),
+    WebSocketClosed(WebSocketClosed),
+    PlayerMigrated(PlayerMigrated),
+    PlayerDropped(PlayerDropped),
+    NodeConnected(NodeConnected),
+    NodeDisconnected(NodeDisconnected),
+    NodeReconnecting(NodeReconnecting),
+    NodeRemoved,
+    TrackQueued(TrackQueued),
+    TrackAdvanced(TrackAdvanced),
+    QueueFinished(QueueFinished)
 }
\ No newline at end of file
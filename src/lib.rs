@@ -5,6 +5,12 @@ pub mod model;
 pub mod events;
 pub mod cluster;
 pub mod builder;
+pub mod balancer;
+pub mod region;
+pub mod reconnect;
+pub mod store;
+pub mod autoplay;
+pub mod presets;
 
 #[cfg(feature = "util")]
 pub mod util;
\ No newline at end of file
@@ -1,5 +1,6 @@
 use serde::{Serialize, Deserialize};
 use serde_aux::prelude::*;
+use crate::error::{AndelinkError, AndelinkResult};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -71,4 +72,287 @@ pub struct Equalize {
 pub struct Band {
     pub band: u8,
     pub gain: f64,
-}
\ No newline at end of file
+}
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigureResuming {
+    pub key: String,
+    pub timeout: u64,
+}
+
+/// Audio filters of a player, filters set to `None` are disabled.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Filters {
+    /// Volume multiplier, from 0 to 5
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub equalizer: Option<Vec<Band>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub karaoke: Option<Karaoke>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timescale: Option<Timescale>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tremolo: Option<Tremolo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vibrato: Option<Vibrato>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<Rotation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distortion: Option<Distortion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_mix: Option<ChannelMix>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub low_pass: Option<LowPass>,
+}
+
+impl Filters {
+    /// Checks that every parameter of the enabled filters is within the range accepted by the server.
+    pub fn validate(&self) -> AndelinkResult<()> {
+        fn check(valid: bool, parameter: &'static str) -> AndelinkResult<()> {
+            if valid { Ok(()) } else { Err(AndelinkError::InvalidFilterValue(parameter)) }
+        }
+
+        if let Some(volume) = self.volume {
+            check((0. ..=5.).contains(&volume), "volume")?;
+        }
+
+        if let Some(bands) = &self.equalizer {
+            for band in bands {
+                check(band.band < 15, "equalizer.band")?;
+                check((-0.25..=1.).contains(&band.gain), "equalizer.gain")?;
+            }
+        }
+
+        if let Some(karaoke) = &self.karaoke {
+            check((0. ..=1.).contains(&karaoke.level), "karaoke.level")?;
+            check((0. ..=1.).contains(&karaoke.mono_level), "karaoke.monoLevel")?;
+            check(karaoke.filter_band >= 0., "karaoke.filterBand")?;
+            check(karaoke.filter_width >= 0., "karaoke.filterWidth")?;
+        }
+
+        if let Some(timescale) = &self.timescale {
+            check(timescale.speed > 0., "timescale.speed")?;
+            check(timescale.pitch > 0., "timescale.pitch")?;
+            check(timescale.rate > 0., "timescale.rate")?;
+        }
+
+        if let Some(tremolo) = &self.tremolo {
+            check(tremolo.frequency > 0., "tremolo.frequency")?;
+            check(tremolo.depth > 0. && tremolo.depth <= 1., "tremolo.depth")?;
+        }
+
+        if let Some(vibrato) = &self.vibrato {
+            check(vibrato.frequency > 0. && vibrato.frequency <= 14., "vibrato.frequency")?;
+            check(vibrato.depth > 0. && vibrato.depth <= 1., "vibrato.depth")?;
+        }
+
+        if let Some(rotation) = &self.rotation {
+            check(rotation.rotation_hz.is_finite(), "rotation.rotationHz")?;
+        }
+
+        if let Some(mix) = &self.channel_mix {
+            check((0. ..=1.).contains(&mix.left_to_left), "channelMix.leftToLeft")?;
+            check((0. ..=1.).contains(&mix.left_to_right), "channelMix.leftToRight")?;
+            check((0. ..=1.).contains(&mix.right_to_left), "channelMix.rightToLeft")?;
+            check((0. ..=1.).contains(&mix.right_to_right), "channelMix.rightToRight")?;
+        }
+
+        if let Some(low_pass) = &self.low_pass {
+            check(low_pass.smoothing >= 0., "lowPass.smoothing")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Removes the vocals of a frequency band.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Karaoke {
+    pub level: f64,
+    pub mono_level: f64,
+    pub filter_band: f64,
+    pub filter_width: f64,
+}
+
+impl Default for Karaoke {
+    fn default() -> Self {
+        Self {
+            level: 1.,
+            mono_level: 1.,
+            filter_band: 220.,
+            filter_width: 100.,
+        }
+    }
+}
+
+/// Changes the speed, pitch and rate of the track.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Timescale {
+    pub speed: f64,
+    pub pitch: f64,
+    pub rate: f64,
+}
+
+impl Default for Timescale {
+    fn default() -> Self {
+        Self {
+            speed: 1.,
+            pitch: 1.,
+            rate: 1.,
+        }
+    }
+}
+
+/// Oscillates the volume.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tremolo {
+    pub frequency: f64,
+    pub depth: f64,
+}
+
+impl Default for Tremolo {
+    fn default() -> Self {
+        Self {
+            frequency: 2.,
+            depth: 0.5,
+        }
+    }
+}
+
+/// Oscillates the pitch.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vibrato {
+    pub frequency: f64,
+    pub depth: f64,
+}
+
+impl Default for Vibrato {
+    fn default() -> Self {
+        Self {
+            frequency: 2.,
+            depth: 0.5,
+        }
+    }
+}
+
+/// Rotates the sound around the stereo channels.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rotation {
+    pub rotation_hz: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Distortion {
+    pub sin_offset: f64,
+    pub sin_scale: f64,
+    pub cos_offset: f64,
+    pub cos_scale: f64,
+    pub tan_offset: f64,
+    pub tan_scale: f64,
+    pub offset: f64,
+    pub scale: f64,
+}
+
+impl Default for Distortion {
+    fn default() -> Self {
+        Self {
+            sin_offset: 0.,
+            sin_scale: 1.,
+            cos_offset: 0.,
+            cos_scale: 1.,
+            tan_offset: 0.,
+            tan_scale: 1.,
+            offset: 0.,
+            scale: 1.,
+        }
+    }
+}
+
+/// Mixes the left and right channels.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelMix {
+    pub left_to_left: f64,
+    pub left_to_right: f64,
+    pub right_to_left: f64,
+    pub right_to_right: f64,
+}
+
+impl Default for ChannelMix {
+    fn default() -> Self {
+        Self {
+            left_to_left: 1.,
+            left_to_right: 0.,
+            right_to_left: 0.,
+            right_to_right: 1.,
+        }
+    }
+}
+
+/// Suppresses the higher frequencies, smoothing values of 1 or less disable it.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LowPass {
+    pub smoothing: f64,
+}
+
+impl Default for LowPass {
+    fn default() -> Self {
+        Self {
+            smoothing: 20.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid(filters: Filters) -> Option<&'static str> {
+        match filters.validate() {
+            Err(AndelinkError::InvalidFilterValue(parameter)) => Some(parameter),
+            _ => None
+        }
+    }
+
+    #[test]
+    fn default_filters_are_valid() {
+        assert!(Filters::default().validate().is_ok());
+
+        let filters = Filters {
+            volume: Some(5.),
+            equalizer: Some(vec![Band { band: 14, gain: -0.25 }]),
+            karaoke: Some(Karaoke::default()),
+            timescale: Some(Timescale::default()),
+            tremolo: Some(Tremolo::default()),
+            vibrato: Some(Vibrato::default()),
+            rotation: Some(Rotation { rotation_hz: 0.2 }),
+            distortion: Some(Distortion::default()),
+            channel_mix: Some(ChannelMix::default()),
+            low_pass: Some(LowPass::default()),
+        };
+
+        assert!(filters.validate().is_ok());
+    }
+
+    #[test]
+    fn out_of_range_values_are_rejected() {
+        assert_eq!(invalid(Filters { volume: Some(5.5), ..Default::default() }), Some("volume"));
+        assert_eq!(invalid(Filters { equalizer: Some(vec![Band { band: 15, gain: 0. }]), ..Default::default() }), Some("equalizer.band"));
+        assert_eq!(invalid(Filters { equalizer: Some(vec![Band { band: 0, gain: 1.5 }]), ..Default::default() }), Some("equalizer.gain"));
+        assert_eq!(invalid(Filters { timescale: Some(Timescale { speed: 0., ..Default::default() }), ..Default::default() }), Some("timescale.speed"));
+        assert_eq!(invalid(Filters { tremolo: Some(Tremolo { depth: 1.5, ..Default::default() }), ..Default::default() }), Some("tremolo.depth"));
+        assert_eq!(invalid(Filters { vibrato: Some(Vibrato { frequency: 15., ..Default::default() }), ..Default::default() }), Some("vibrato.frequency"));
+        assert_eq!(invalid(Filters { rotation: Some(Rotation { rotation_hz: f64::NAN }), ..Default::default() }), Some("rotation.rotationHz"));
+        assert_eq!(invalid(Filters { channel_mix: Some(ChannelMix { left_to_right: -0.1, ..Default::default() }), ..Default::default() }), Some("channelMix.leftToRight"));
+        assert_eq!(invalid(Filters { low_pass: Some(LowPass { smoothing: -1. }), ..Default::default() }), Some("lowPass.smoothing"));
+    }
+}
@@ -0,0 +1,158 @@
+use super::events::{Filters, Band, Karaoke, Timescale, Tremolo, Vibrato, Rotation, Distortion, ChannelMix, LowPass};
+use super::Codes;
+use crate::error::AndelinkResult;
+use crate::node::UniversalNode;
+
+type FilterUpdate = Box<dyn FnOnce(&mut Filters) + Send>;
+
+/// Changes the audio filters of a player, merging them with the ones it already has.
+///
+/// Every setter takes an `Option`, `None` disables that filter.
+pub struct FiltersBuilder<'a> {
+    node: &'a UniversalNode,
+    guild_id: u64,
+    reset: bool,
+    preset: Option<String>,
+    updates: Vec<FilterUpdate>
+}
+
+impl<'a> FiltersBuilder<'a> {
+    pub(crate) fn new(node: &'a UniversalNode, guild_id: u64) -> Self {
+        Self {
+            node,
+            guild_id,
+            reset: false,
+            preset: None,
+            updates: Vec::new()
+        }
+    }
+
+    fn update(mut self, update: impl FnOnce(&mut Filters) + Send + 'static) -> Self {
+        self.updates.push(Box::new(update));
+        self
+    }
+
+    /// Disables every filter the player had before applying the ones set on this builder.
+    pub fn reset(mut self) -> Self {
+        self.reset = true;
+        self
+    }
+
+    /// Replaces every filter with the ones of a preset, which becomes the active preset of the player.
+    pub(crate) fn preset(mut self, name: &str, preset: Filters) -> Self {
+        self.reset = true;
+        self.preset = Some(name.to_lowercase());
+        self.update(move |filters| *filters = preset)
+    }
+
+    /// Sets the volume multiplier, from 0 to 5.
+    pub fn volume(self, volume: impl Into<Option<f64>>) -> Self {
+        let volume = volume.into();
+        self.update(move |filters| filters.volume = volume)
+    }
+
+    /// Sets the equalizer bands, the gains of the bands not given are reset.
+    pub fn equalizer(self, bands: impl Into<Option<Vec<Band>>>) -> Self {
+        let bands = bands.into();
+        self.update(move |filters| filters.equalizer = bands)
+    }
+
+    pub fn karaoke(self, karaoke: impl Into<Option<Karaoke>>) -> Self {
+        let karaoke = karaoke.into();
+        self.update(move |filters| filters.karaoke = karaoke)
+    }
+
+    pub fn timescale(self, timescale: impl Into<Option<Timescale>>) -> Self {
+        let timescale = timescale.into();
+        self.update(move |filters| filters.timescale = timescale)
+    }
+
+    pub fn tremolo(self, tremolo: impl Into<Option<Tremolo>>) -> Self {
+        let tremolo = tremolo.into();
+        self.update(move |filters| filters.tremolo = tremolo)
+    }
+
+    pub fn vibrato(self, vibrato: impl Into<Option<Vibrato>>) -> Self {
+        let vibrato = vibrato.into();
+        self.update(move |filters| filters.vibrato = vibrato)
+    }
+
+    pub fn rotation(self, rotation: impl Into<Option<Rotation>>) -> Self {
+        let rotation = rotation.into();
+        self.update(move |filters| filters.rotation = rotation)
+    }
+
+    pub fn distortion(self, distortion: impl Into<Option<Distortion>>) -> Self {
+        let distortion = distortion.into();
+        self.update(move |filters| filters.distortion = distortion)
+    }
+
+    pub fn channel_mix(self, channel_mix: impl Into<Option<ChannelMix>>) -> Self {
+        let channel_mix = channel_mix.into();
+        self.update(move |filters| filters.channel_mix = channel_mix)
+    }
+
+    pub fn low_pass(self, low_pass: impl Into<Option<LowPass>>) -> Self {
+        let low_pass = low_pass.into();
+        self.update(move |filters| filters.low_pass = low_pass)
+    }
+
+    /// Validates the resulting filters and sends them to the server.
+    pub async fn apply(self) -> AndelinkResult<()> {
+        let Self { node, guild_id, reset, preset, updates } = self;
+
+        let (filters, equalizer) = node.with_player(guild_id, |player| {
+            let mut filters = if reset { Filters::default() } else { player.filters.clone() };
+
+            if !reset {
+                filters.equalizer = Some(bands(&player.equalizer));
+            }
+
+            for update in updates {
+                update(&mut filters);
+            }
+
+            filters.validate()?;
+
+            // The equalizer lives on the player so it stays in sync with the `equalizer` op
+            let mut equalizer = [0.; 15];
+
+            for band in filters.equalizer.take().unwrap_or_default() {
+                equalizer[band.band as usize] = band.gain;
+            }
+
+            Ok((filters, equalizer))
+        })?;
+
+        node.send(guild_id, Codes::Filters(payload(&filters, &equalizer)))?;
+
+        node.with_player(guild_id, |player| {
+            player.filters = filters;
+            player.equalizer = equalizer;
+            player.preset = preset;
+
+            Ok(())
+        })
+    }
+}
+
+/// Converts the gains of an equalizer into bands.
+pub(crate) fn bands(equalizer: &[f64; 15]) -> Vec<Band> {
+    equalizer.iter().enumerate().map(|(index, gain)| {
+        Band {
+            band: index as u8,
+            gain: *gain,
+        }
+    }).collect()
+}
+
+/// Payload of the `filters` op, which replaces every filter of the player on the server, equalizer included.
+pub(crate) fn payload(filters: &Filters, equalizer: &[f64; 15]) -> Filters {
+    let mut payload = filters.clone();
+
+    if equalizer.iter().any(|gain| *gain != 0.) {
+        payload.equalizer = Some(bands(equalizer));
+    }
+
+    payload
+}
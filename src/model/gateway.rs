@@ -1,5 +1,6 @@
 use serde::{Serialize, Deserialize};
 use serde_aux::prelude::*;
+use super::track::Exception;
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct GatewayEvent {
@@ -103,6 +104,31 @@ pub struct TrackFinish {
     pub guild_id: u64,
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TrackStuck {
+    pub op: String,
+    #[serde(rename = "type")]
+    pub track_stuck_type: String,
+    pub track: String,
+    #[serde(rename = "guildId")]
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub guild_id: u64,
+    #[serde(rename = "thresholdMs")]
+    pub threshold_ms: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TrackException {
+    pub op: String,
+    #[serde(rename = "type")]
+    pub track_exception_type: String,
+    pub track: String,
+    #[serde(rename = "guildId")]
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub guild_id: u64,
+    pub exception: Exception,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct WebSocketClosed {
     pub op: String,
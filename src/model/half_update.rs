@@ -12,7 +12,6 @@ use crate::node::UniversalNode;
 use crate::model::events::{Event, VoiceUpdate};
 use crate::error::{
     ClusterError,
-    AndelinkError,
     AndelinkResult,
     ClusterResult
 };
@@ -25,12 +24,10 @@ pub enum HalfVoiceUpdate {
 
 impl HalfVoiceUpdate {
     pub async fn process(self, node: Arc<UniversalNode>) -> ClusterResult<()> {
-        let node_read = node.read().await;
-
         let guild_id = match &self {
             HalfVoiceUpdate::Server(e) => e.guild_id,
             HalfVoiceUpdate::State(e) => {
-                let client_id = node_read.id;
+                let client_id = node.id;
 
                 if e.user_id != client_id {
                     return Ok(())
@@ -48,7 +45,7 @@ impl HalfVoiceUpdate {
         info!("Processing HalfVoiceUpdate event: {:?}", &self);
 
         let update = {
-            let existing_half = match node_read.waiting.get(&guild_id) {
+            let existing_half = match node.waiting.get(&guild_id) {
                 Some(half) => half.value().clone(),
                 None => {
                     info!(
@@ -57,7 +54,7 @@ impl HalfVoiceUpdate {
                         &self
                     );
 
-                    node_read.waiting.insert(guild_id, self);
+                    node.waiting.insert(guild_id, self);
 
                     return Ok(());
                 }
@@ -78,7 +75,7 @@ impl HalfVoiceUpdate {
                         guild_id,
                         state
                     );
-                    node_read
+                    node
                         .waiting
                         .insert(guild_id, HalfVoiceUpdate::State(state));
 
@@ -86,7 +83,7 @@ impl HalfVoiceUpdate {
                 }
                 (HalfVoiceUpdate::State(ref state), HalfVoiceUpdate::Server(ref server)) => {
 
-                    node_read
+                    node
                         .waiting
                         .remove(&guild_id);
 
@@ -109,7 +106,7 @@ impl HalfVoiceUpdate {
                         guild_id,
                         server
                     );
-                    node_read
+                    node
                         .waiting
                         .insert(guild_id, HalfVoiceUpdate::Server(server));
 
@@ -117,7 +114,7 @@ impl HalfVoiceUpdate {
                 }
                 (HalfVoiceUpdate::Server(ref server), HalfVoiceUpdate::State(ref state)) => {
 
-                    node_read
+                    node
                         .waiting
                         .remove(&guild_id);
 
@@ -135,22 +132,18 @@ impl HalfVoiceUpdate {
             }
         };
 
-        drop(node_read);
-
         info!("sending voice update for guild {}: {:?}", guild_id, update);
 
-        send(node.clone(), guild_id, update).await?;
+        send(&node, guild_id, update)?;
 
         Ok(())
     }
 }
 
-async fn send(node: Arc<UniversalNode>, guild_id: GuildId, payload: VoiceUpdate) -> AndelinkResult<()> {
-    let mut node_write = node.write().await;
-
-    if let Some(socket) = &mut node_write.socket_write {
-        crate::model::Codes::VoiceUpdate(payload).send(guild_id, socket).await
-    } else {
-        return Err(AndelinkError::NoWebsocket);
+fn send(node: &UniversalNode, guild_id: GuildId, payload: VoiceUpdate) -> AndelinkResult<()> {
+    if let Some(mut player) = node.players.get_mut(&guild_id.0) {
+        player.voice_update = Some(payload.clone());
     }
-}
\ No newline at end of file
+
+    node.send(guild_id, crate::model::Codes::VoiceUpdate(payload))
+}
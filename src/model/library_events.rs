@@ -0,0 +1,66 @@
+//! Events generated by the library itself instead of being sent by the audio server.
+
+use super::track::QueuedTrack;
+
+#[derive(Debug, Clone)]
+pub struct PlayerMigrated {
+    /// Guild whose player was migrated
+    pub guild_id: u64,
+    /// Id of the node that was removed from cluster
+    pub from_node: u8,
+    /// Id of the node that now holds the player
+    pub to_node: u8,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlayerDropped {
+    /// Guild whose player was lost
+    pub guild_id: u64,
+    /// Why the player couldn't be recreated
+    pub reason: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeConnected {
+    /// Whether the previous session was resumed, keeping all players
+    pub resumed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeDisconnected {
+    /// Close code sent by the server, `None` if the connection was lost without a close frame
+    pub code: Option<u16>,
+    /// Close reason sent by the server, or the error that caused the disconnection
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeReconnecting {
+    /// Number of the reconnect attempt, starting from 1
+    pub attempt: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackQueued {
+    pub guild_id: u64,
+    /// Track added to the queue
+    pub track: QueuedTrack,
+    /// Position of the track on the upcoming tracks, `None` if it started playing right away
+    pub position: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackAdvanced {
+    pub guild_id: u64,
+    /// Track that was playing before, `None` if the player was idle
+    pub previous: Option<QueuedTrack>,
+    /// Track that started playing
+    pub current: QueuedTrack,
+}
+
+#[derive(Debug, Clone)]
+pub struct QueueFinished {
+    pub guild_id: u64,
+    /// Last track played
+    pub last: Option<QueuedTrack>,
+}
@@ -4,19 +4,18 @@ pub mod gateway;
 pub mod play_parameters;
 pub mod player;
 pub mod half_update;
+pub mod library_events;
+pub mod filters;
 
 use serde::{Serialize, Deserialize};
 
 use events::*;
 use tokio_tungstenite::tungstenite::Message as TungsteniteMessage;
-use crate::types::WebSocketConnection;
 use serenity::model::id::GuildId as DiscordGuildId;
 use serde_json::{
     json,
     Value
 };
-use crate::error::{AndelinkError, AndelinkResult};
-use futures::SinkExt;
 
 pub fn merge(a: &mut Value, b: Value) {
     match (a, b) {
@@ -53,11 +52,14 @@ pub enum Codes {
     //Updates position information
     PlayerUpdate(PlayerUpdate),
     //Change the player's volume
-    Volume(Volume)
+    Volume(Volume),
+    //Replace the player's audio filters
+    Filters(Filters)
 }
 
 impl Codes {
-    pub async fn send(&self, guild_id: impl Into<DiscordGuildId>, socket: &mut WebSocketConnection) -> AndelinkResult<()>{
+    /// Serializes the payload into the message sent to the server.
+    pub fn to_message(&self, guild_id: impl Into<DiscordGuildId>) -> TungsteniteMessage {
         let value = match self {
             Self::Destroy => {
                 json!({
@@ -119,6 +121,14 @@ impl Codes {
                 merge(&mut x, serde_json::to_value(data).unwrap());
                 x
             },
+            Self::Filters(data) => {
+                let mut x = json!({
+                    "op" : "filters",
+                    "guildId" : &guild_id.into().0.to_string(),
+                });
+                merge(&mut x, serde_json::to_value(data).unwrap());
+                x
+            },
             Self::PlayerUpdate(data) => {
                 let mut x = json!({
                     "op" : "playerUpdate",
@@ -129,14 +139,38 @@ impl Codes {
             }
         };
 
-        let payload = serde_json::to_string(&value).unwrap();
+        to_text_message(value)
+    }
+}
 
-        {
-            if let Err(why) = socket.send(TungsteniteMessage::text(&payload)).await {
-                return Err(AndelinkError::ErrorSendingPayload(why));
-            };
-        }
+/// Payloads that are not bound to any guild and affect the whole node session.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(rename_all = "camelCase")]
+pub enum NodeCodes {
+    //Configure the key and timeout used to resume the session
+    ConfigureResuming(ConfigureResuming)
+}
 
-        Ok(())
+impl NodeCodes {
+    /// Serializes the payload into the message sent to the server.
+    pub fn to_message(&self) -> TungsteniteMessage {
+        let value = match self {
+            Self::ConfigureResuming(data) => {
+                let mut x = json!({
+                    "op" : "configureResuming",
+                });
+                merge(&mut x, serde_json::to_value(data).unwrap());
+                x
+            }
+        };
+
+        to_text_message(value)
     }
-}
\ No newline at end of file
+}
+
+fn to_text_message(value: Value) -> TungsteniteMessage {
+    let payload = serde_json::to_string(&value).unwrap();
+
+    TungsteniteMessage::text(payload)
+}
@@ -1,14 +1,14 @@
 use serenity::{
     model::id::ChannelId as DiscordChannelId
 };
-use super::track::Track;
-use crate::error::{AndelinkResult, AndelinkError};
+use super::track::{Track, QueuedTrack};
+use crate::error::AndelinkResult;
 use std::{
     time::Duration,
 };
 use crate::node::UniversalNode;
-use crate::error::AndelinkError::PlayerNotFound;
 use super::track::TrackRequester;
+use super::player::QueueOutcome;
 
 pub struct PlayParameters<'a> {
     node: &'a UniversalNode,
@@ -18,7 +18,9 @@ pub struct PlayParameters<'a> {
     pub finish: u64,
     pub guild_id: u64,
     pub requester: Option<TrackRequester>,
-    pub channel: Option<DiscordChannelId>
+    pub channel: Option<DiscordChannelId>,
+    pub fade_in: Option<Duration>,
+    pub fade_out: Option<Duration>
 }
 
 impl<'a> PlayParameters<'a> {
@@ -33,60 +35,62 @@ impl<'a> PlayParameters<'a> {
             finish: Default::default(),
             guild_id: Default::default(),
             requester: Default::default(),
-            channel: Default::default()
+            channel: Default::default(),
+            fade_in: Default::default(),
+            fade_out: Default::default()
         }
     }
 
-    /// Starts playing the track.
+    /// Starts playing the track right away, making it the current track of the guild.
+    ///
+    /// The upcoming tracks of the queue are kept. If something is playing, the track only replaces it if `replace` is set.
     pub async fn start(self) -> AndelinkResult<()> {
-        let mut write = self.node.write().await;
+        let node = self.node;
+        let guild_id = self.guild_id;
+        let replace = self.replace;
 
-        let socket = if let Some(stream) = &mut write.socket_write { stream } else { return Err(AndelinkError::NoWebsocket) };
-
-        let payload = crate::model::events::Play {
-            track: self.track.track,
-            no_replace: !self.replace,
-            start_time: self.start,
-            end_time: if self.finish == 0 { None } else { Some(self.finish) },
-        };
-
-        crate::model::Codes::Play(payload).send(self.guild_id, socket).await?;
-
-
-        Ok(())
+        node.play_now(guild_id, self.into_queued_track(), replace)
     }
 
-    pub async fn queue(self) -> AndelinkResult<()> {
-        let should_start = {
-            let node_read = self.node.read().await;
-            if let Some(player) = node_read.players.get(&self.guild_id) {
-                player.now_playing.is_none() && player.queue.len() == 0
-            } else {
-                false
-            }
-        };
-
-        let track = crate::model::track::QueuedTrack {
+    fn into_queued_track(self) -> QueuedTrack {
+        QueuedTrack {
             track: self.track,
             start_time: self.start,
             end_time: if self.finish == 0 { None } else { Some(self.finish) },
             requester: self.requester,
-            channel: self.channel
-        };
+            channel: self.channel,
+            fade_in: self.fade_in,
+            fade_out: self.fade_out
+        }
+    }
 
-        let mut node_write = self.node.write().await;
+    /// Adds the track to the queue, starting it if nothing is playing.
+    ///
+    /// The track goes to the end of the queue unless the fair queue is enabled for the guild.
+    /// Fails if the track breaks any of the queue limits of the guild, or if it's a duplicate
+    /// and the guild rejects them.
+    pub async fn queue(self) -> AndelinkResult<QueueOutcome> {
+        let node = self.node;
+        let guild_id = self.guild_id;
 
-        if let Some(player) = node_write.players.get_mut(&self.guild_id) {
-            player.queue.push(track);
-        } else {
-            return Err(PlayerNotFound);
-        }
+        node.queue_track(guild_id, self.into_queued_track(), None)
+    }
 
-        if should_start {
-            node_write.play_next(self.guild_id).await?;
-        }
+    /// Inserts the track at the given position of the upcoming tracks, 0 being the next track.
+    ///
+    /// Follows the same duplicate policy and queue limits as [`queue`].
+    ///
+    /// [`queue`]: Self::queue
+    pub async fn queue_at(self, index: usize) -> AndelinkResult<QueueOutcome> {
+        let node = self.node;
+        let guild_id = self.guild_id;
+
+        node.queue_track(guild_id, self.into_queued_track(), Some(index))
+    }
 
-        Ok(())
+    /// Inserts the track so it plays right after the current one.
+    pub async fn queue_next(self) -> AndelinkResult<QueueOutcome> {
+        self.queue_at(0).await
     }
 
     /// Sets the person that requested the song
@@ -120,6 +124,22 @@ impl<'a> PlayParameters<'a> {
         self
     }
 
+    /// Fades the volume in when the track starts, either from the queue or with [`start`].
+    ///
+    /// [`start`]: Self::start
+    pub fn fade_in(mut self, duration: Duration) -> Self {
+        self.fade_in = Some(duration);
+        self
+    }
+
+    /// Fades the volume out before the track finishes, either from the queue or with [`start`].
+    ///
+    /// [`start`]: Self::start
+    pub fn fade_out(mut self, duration: Duration) -> Self {
+        self.fade_out = Some(duration);
+        self
+    }
+
     /// Sets the person that requested the song taking a mutable reference
     pub fn requester_ref(&mut self, requester: impl Into<TrackRequester>) -> &mut Self {
         self.requester = Some(requester.into());
@@ -150,4 +170,20 @@ impl<'a> PlayParameters<'a> {
 
         self
     }
+
+    /// Fades the volume in when the track starts taking a mutable reference, see [`fade_in`].
+    ///
+    /// [`fade_in`]: Self::fade_in
+    pub fn fade_in_ref(&mut self, duration: Duration) -> &mut Self {
+        self.fade_in = Some(duration);
+        self
+    }
+
+    /// Fades the volume out before the track finishes taking a mutable reference, see [`fade_out`].
+    ///
+    /// [`fade_out`]: Self::fade_out
+    pub fn fade_out_ref(&mut self, duration: Duration) -> &mut Self {
+        self.fade_out = Some(duration);
+        self
+    }
 }
\ No newline at end of file
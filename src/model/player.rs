@@ -1,7 +1,20 @@
 use serenity::{
     model::id::GuildId as DiscordGuildId
 };
-use super::track::QueuedTrack;
+use serde::{Deserialize, Serialize};
+use super::track::{QueuedTrack, Track, TrackRequester};
+use super::events::{VoiceUpdate, Filters};
+use crate::{error::{AndelinkError, AndelinkResult}, autoplay::AutoplayProvider};
+use std::{
+    collections::VecDeque,
+    sync::Arc,
+    ops::{RangeBounds, Bound},
+    time::Duration
+};
+use rand::seq::SliceRandom;
+
+/// Amount of played tracks kept by default on the history of a player.
+pub const DEFAULT_HISTORY_SIZE: usize = 25;
 
 #[derive(Clone)]
 pub struct Player {
@@ -9,7 +22,31 @@ pub struct Player {
     pub now_playing: Option<QueuedTrack>,
     pub paused: bool,
     pub volume: u16,
-    pub queue: Vec<QueuedTrack>
+    pub queue: Vec<QueuedTrack>,
+    /// Gains of the 15 equalizer bands
+    pub equalizer: [f64; 15],
+    /// Last voice update sent for this player, used to recreate it on another node
+    pub voice_update: Option<VoiceUpdate>,
+    /// How the queue advances when a track finishes
+    pub loop_mode: LoopMode,
+    /// Played tracks, the most recent one being at the back
+    pub history: VecDeque<QueuedTrack>,
+    /// Maximum amount of tracks kept on the history, 0 disables it
+    pub history_size: usize,
+    /// Whether tracks of different requesters are interleaved when queued
+    pub fair_queue: bool,
+    /// Limits checked before adding tracks to the queue
+    pub limits: QueueLimits,
+    /// What to do when a track that is already queued is queued again
+    pub duplicate_policy: DuplicatePolicy,
+    /// Provider asked for a new track when the queue runs dry
+    pub autoplay: Option<Arc<dyn AutoplayProvider>>,
+    /// Audio filters applied with the `filters` op, the equalizer is always kept on `equalizer`
+    pub filters: Filters,
+    /// Name of the preset the filters come from, cleared when they are changed by hand
+    pub preset: Option<String>,
+    /// Volume to go back to when the next track starts, kept while the fades of a track change the volume
+    pub fade_base: Option<u16>
 }
 
 impl Default for Player {
@@ -19,7 +56,598 @@ impl Default for Player {
             now_playing: None,
             paused: false,
             volume: 100,
-            queue: vec![]
+            queue: vec![],
+            equalizer: [0.; 15],
+            voice_update: None,
+            loop_mode: LoopMode::Off,
+            history: VecDeque::new(),
+            history_size: DEFAULT_HISTORY_SIZE,
+            fair_queue: false,
+            limits: QueueLimits::default(),
+            duplicate_policy: DuplicatePolicy::Allow,
+            autoplay: None,
+            filters: Filters::default(),
+            preset: None,
+            fade_base: None
+        }
+    }
+}
+
+impl Player {
+    /// Position in `queue` where the upcoming tracks start, skipping the one currently playing.
+    fn upcoming_start(&self) -> usize {
+        if self.now_playing.is_some() && !self.queue.is_empty() { 1 } else { 0 }
+    }
+
+    /// Tracks that will be played after the current one, index 0 being the next track.
+    pub fn upcoming(&self) -> &[QueuedTrack] {
+        &self.queue[self.upcoming_start()..]
+    }
+
+    /// Time left for the current track to finish, `None` if nothing is playing or it's a stream.
+    pub fn current_remaining(&self) -> Option<Duration> {
+        self.now_playing.as_ref().and_then(|current| current.remaining())
+    }
+
+    /// Time left to play the whole queue, including the rest of the current track. Streams are not counted.
+    pub fn queue_remaining(&self) -> Duration {
+        let upcoming = self.upcoming().iter()
+            .filter_map(|track| track.duration())
+            .sum::<Duration>();
+
+        self.current_remaining().unwrap_or_default() + upcoming
+    }
+
+    /// Time until the upcoming track at the given position starts playing, 0 being the next track.
+    /// Streams before it are not counted.
+    ///
+    /// Returns `None` if there is no track at that position.
+    pub fn time_until(&self, index: usize) -> Option<Duration> {
+        let upcoming = self.upcoming();
+
+        if index >= upcoming.len() {
+            return None;
+        }
+
+        let before = upcoming[..index].iter()
+            .filter_map(|track| track.duration())
+            .sum::<Duration>();
+
+        Some(self.current_remaining().unwrap_or_default() + before)
+    }
+
+    /// Adds a track to the queue, interleaving it with the tracks of other requesters if
+    /// the fair queue is enabled.
+    ///
+    /// Returns the position of the track on `queue`.
+    pub(crate) fn enqueue(&mut self, track: QueuedTrack) -> usize {
+        if !self.fair_queue {
+            self.queue.push(track);
+            return self.queue.len() - 1;
+        }
+
+        let start = self.upcoming_start();
+        let index = start + fair_position(&self.queue[start..], track.requester.as_ref());
+
+        self.queue.insert(index, track);
+
+        index
+    }
+
+    /// Adds a track to the queue following the duplicate policy and the limits of the player.
+    ///
+    /// The track goes to the given position of the upcoming tracks, or where `enqueue` puts it if there is none.
+    pub(crate) fn queue_track(&mut self, track: QueuedTrack, position: Option<usize>) -> AndelinkResult<QueueOutcome> {
+        if position.map_or(false, |position| position > self.upcoming().len()) {
+            return Err(AndelinkError::QueueIndexOutOfBounds);
+        }
+
+        let playing = self.now_playing.as_ref()
+            .map_or(false, |current| current.track.is_same(&track.track));
+
+        let duplicate = self.upcoming().iter()
+            .position(|queued| queued.track.is_same(&track.track));
+
+        match (self.duplicate_policy, duplicate) {
+            (DuplicatePolicy::Reject, _) if playing || duplicate.is_some() => {
+                return Err(AndelinkError::DuplicateTrack);
+            },
+            (DuplicatePolicy::MoveToEnd, Some(index)) => {
+                let existing = self.remove_upcoming(index)?;
+
+                return Ok(QueueOutcome::Moved(self.place(existing, position)));
+            },
+            _ => ()
+        }
+
+        self.check_limits(vec![(&track.track, track.requester.as_ref())])?;
+
+        let should_start = self.now_playing.is_none() && self.queue.is_empty();
+        let index = self.place(track, position);
+
+        if should_start {
+            Ok(QueueOutcome::Started)
+        } else {
+            Ok(QueueOutcome::Queued(index))
         }
     }
-}
\ No newline at end of file
+
+    /// Puts a track on the given position of the upcoming tracks, or enqueues it if there is none.
+    ///
+    /// Returns the position of the track on the upcoming tracks.
+    fn place(&mut self, track: QueuedTrack, position: Option<usize>) -> usize {
+        match position {
+            Some(position) => {
+                let position = std::cmp::min(position, self.upcoming().len());
+                let start = self.upcoming_start();
+
+                self.queue.insert(start + position, track);
+
+                position
+            },
+            None => {
+                let index = self.enqueue(track);
+
+                index - self.upcoming_start()
+            }
+        }
+    }
+
+    /// Checks if the given tracks, with their requesters, can be added to the queue without breaking its limits.
+    pub(crate) fn check_limits<'a, I>(&self, tracks: I) -> AndelinkResult<()>
+    where
+        I: IntoIterator<Item = (&'a Track, Option<&'a TrackRequester>)>
+    {
+        let limits = &self.limits;
+        let tracks = tracks.into_iter().collect::<Vec<_>>();
+        let upcoming = self.upcoming();
+
+        if let Some(max) = limits.max_length {
+            if upcoming.len() + tracks.len() > max {
+                return Err(AndelinkError::QueueFull(max));
+            }
+        }
+
+        for (index, (track, requester)) in tracks.iter().enumerate() {
+            if let Some(info) = &track.info {
+                if info.is_stream && !limits.allow_streams {
+                    return Err(AndelinkError::StreamsNotAllowed);
+                }
+
+                if let Some(max) = limits.max_track_length {
+                    if !info.is_stream && info.length > max.as_millis() as u64 {
+                        return Err(AndelinkError::TrackTooLong(max));
+                    }
+                }
+            }
+
+            if let (Some(max), Some(requester)) = (limits.max_per_requester, requester) {
+                let queued = upcoming.iter()
+                    .filter(|queued| queued.requester.as_ref().map_or(false, |r| r.is_same(requester)))
+                    .count();
+
+                let added = tracks[..=index].iter()
+                    .filter(|(_, r)| r.map_or(false, |r| r.is_same(requester)))
+                    .count();
+
+                if queued + added > max {
+                    return Err(AndelinkError::RequesterQuotaExceeded(max));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records a played track on the history, dropping the oldest one if it is full.
+    pub(crate) fn push_history(&mut self, track: QueuedTrack) {
+        if self.history_size == 0 {
+            return;
+        }
+
+        while self.history.len() >= self.history_size {
+            self.history.pop_front();
+        }
+
+        self.history.push_back(track);
+    }
+
+    pub(crate) fn set_history_size(&mut self, size: usize) {
+        self.history_size = size;
+
+        while self.history.len() > size {
+            self.history.pop_front();
+        }
+    }
+
+    pub(crate) fn remove_upcoming(&mut self, index: usize) -> AndelinkResult<QueuedTrack> {
+        if index >= self.upcoming().len() {
+            return Err(AndelinkError::QueueIndexOutOfBounds);
+        }
+
+        let start = self.upcoming_start();
+
+        Ok(self.queue.remove(start + index))
+    }
+
+    pub(crate) fn remove_upcoming_range(&mut self, range: impl RangeBounds<usize>) -> AndelinkResult<Vec<QueuedTrack>> {
+        let len = self.upcoming().len();
+
+        let from = match range.start_bound() {
+            Bound::Included(from) => *from,
+            Bound::Excluded(from) => *from + 1,
+            Bound::Unbounded => 0
+        };
+
+        let to = match range.end_bound() {
+            Bound::Included(to) => *to + 1,
+            Bound::Excluded(to) => *to,
+            Bound::Unbounded => len
+        };
+
+        if from > to || to > len {
+            return Err(AndelinkError::QueueIndexOutOfBounds);
+        }
+
+        let start = self.upcoming_start();
+
+        Ok(self.queue.drain(start + from..start + to).collect())
+    }
+
+    pub(crate) fn move_upcoming(&mut self, from: usize, to: usize) -> AndelinkResult<()> {
+        let len = self.upcoming().len();
+
+        if from >= len || to >= len {
+            return Err(AndelinkError::QueueIndexOutOfBounds);
+        }
+
+        let start = self.upcoming_start();
+        let track = self.queue.remove(start + from);
+
+        self.queue.insert(start + to, track);
+
+        Ok(())
+    }
+
+    pub(crate) fn clear_upcoming(&mut self) -> Vec<QueuedTrack> {
+        let start = self.upcoming_start();
+
+        self.queue.drain(start..).collect()
+    }
+
+    pub(crate) fn shuffle_upcoming(&mut self) {
+        let start = self.upcoming_start();
+
+        self.queue[start..].shuffle(&mut rand::thread_rng());
+    }
+
+    pub(crate) fn reverse_upcoming(&mut self) {
+        let start = self.upcoming_start();
+
+        self.queue[start..].reverse();
+    }
+}
+
+fn same_requester(a: Option<&TrackRequester>, b: Option<&TrackRequester>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.is_same(b),
+        (None, None) => true,
+        _ => false
+    }
+}
+
+/// Position of the upcoming tracks where a track of the given requester should go so every
+/// requester gets one track per round: A1, B1, C1, A2, B2...
+fn fair_position(upcoming: &[QueuedTrack], requester: Option<&TrackRequester>) -> usize {
+    // Round the new track belongs to
+    let round = upcoming.iter()
+        .filter(|queued| same_requester(queued.requester.as_ref(), requester))
+        .count();
+
+    let mut counts: Vec<(Option<&TrackRequester>, usize)> = Vec::new();
+    let mut position = 0;
+
+    for (index, queued) in upcoming.iter().enumerate() {
+        let queued_requester = queued.requester.as_ref();
+
+        let track_round = match counts.iter_mut().find(|(r, _)| same_requester(*r, queued_requester)) {
+            Some((_, count)) => {
+                *count += 1;
+                *count - 1
+            },
+            None => {
+                counts.push((queued_requester, 1));
+                0
+            }
+        };
+
+        if track_round <= round {
+            position = index + 1;
+        }
+    }
+
+    position
+}
+
+/// Limits of the queue of a player, by default there are none.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueLimits {
+    /// Maximum amount of upcoming tracks
+    pub max_length: Option<usize>,
+    /// Maximum amount of upcoming tracks of a single requester
+    pub max_per_requester: Option<usize>,
+    /// Maximum length of a track, streams are not affected
+    pub max_track_length: Option<Duration>,
+    /// Whether streams can be queued
+    pub allow_streams: bool
+}
+
+impl Default for QueueLimits {
+    fn default() -> Self {
+        Self {
+            max_length: None,
+            max_per_requester: None,
+            max_track_length: None,
+            allow_streams: true
+        }
+    }
+}
+
+impl QueueLimits {
+    /// Sets the maximum amount of upcoming tracks.
+    pub fn max_length(mut self, max: usize) -> Self {
+        self.max_length = Some(max);
+        self
+    }
+
+    /// Sets the maximum amount of upcoming tracks of a single requester.
+    pub fn max_per_requester(mut self, max: usize) -> Self {
+        self.max_per_requester = Some(max);
+        self
+    }
+
+    /// Sets the maximum length of a track.
+    pub fn max_track_length(mut self, max: Duration) -> Self {
+        self.max_track_length = Some(max);
+        self
+    }
+
+    /// Sets whether streams can be queued.
+    pub fn allow_streams(mut self, allow: bool) -> Self {
+        self.allow_streams = allow;
+        self
+    }
+}
+
+/// Defines what happens when a track that is already playing or queued is queued again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuplicatePolicy {
+    /// The track is queued again
+    Allow,
+    /// The track is rejected with `AndelinkError::DuplicateTrack`
+    Reject,
+    /// The queued track is moved to where the new one would go instead of adding another one,
+    /// the end of the queue unless the fair queue is enabled or a position is given
+    MoveToEnd
+}
+
+impl Default for DuplicatePolicy {
+    fn default() -> Self {
+        Self::Allow
+    }
+}
+
+/// Result of adding a track to the queue.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QueueOutcome {
+    /// Nothing was playing, so the track started playing
+    Started,
+    /// The track was added at the given position of the upcoming tracks
+    Queued(usize),
+    /// The track was already queued and was moved to the given position of the upcoming tracks
+    Moved(usize)
+}
+
+/// Defines what happens to a track once it finishes playing.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum LoopMode {
+    /// The track is removed from the queue
+    Off,
+    /// The current track is played again
+    Track,
+    /// The track is moved to the end of the queue
+    Queue
+}
+
+impl Default for LoopMode {
+    fn default() -> Self {
+        LoopMode::Off
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::track::TrackInfo;
+
+    fn track(id: &str, length: u64, requester: Option<&str>) -> QueuedTrack {
+        QueuedTrack {
+            track: Track {
+                track: id.to_string(),
+                info: Some(TrackInfo {
+                    identifier: id.to_string(),
+                    is_seekable: true,
+                    length,
+                    ..Default::default()
+                })
+            },
+            requester: requester.map(|name| TrackRequester::from(name.to_string())),
+            ..Default::default()
+        }
+    }
+
+    fn stream(id: &str) -> QueuedTrack {
+        let mut stream = track(id, 0, None);
+
+        if let Some(info) = stream.track.info.as_mut() {
+            info.is_stream = true;
+            info.is_seekable = false;
+        }
+
+        stream
+    }
+
+    /// Player playing the given track, which is also the first one of the queue.
+    fn playing(current: QueuedTrack) -> Player {
+        Player {
+            now_playing: Some(current.clone()),
+            queue: vec![current],
+            ..Default::default()
+        }
+    }
+
+    fn ids(tracks: &[QueuedTrack]) -> Vec<&str> {
+        tracks.iter().map(|queued| queued.track.track.as_str()).collect()
+    }
+
+    #[test]
+    fn fair_queue_interleaves_requesters() {
+        let mut player = Player {
+            fair_queue: true,
+            ..Default::default()
+        };
+
+        for &(id, requester) in [("a1", "a"), ("a2", "a"), ("a3", "a"), ("b1", "b"), ("b2", "b"), ("c1", "c")].iter() {
+            player.queue_track(track(id, 1000, Some(requester)), None).unwrap();
+        }
+
+        assert_eq!(ids(&player.queue), ["a1", "b1", "c1", "a2", "b2", "a3"]);
+    }
+
+    #[test]
+    fn fair_queue_ignores_current_track() {
+        let mut player = playing(track("b0", 1000, Some("b")));
+        player.fair_queue = true;
+
+        player.queue_track(track("a1", 1000, Some("a")), None).unwrap();
+        player.queue_track(track("a2", 1000, Some("a")), None).unwrap();
+
+        assert_eq!(player.queue_track(track("b1", 1000, Some("b")), None).unwrap(), QueueOutcome::Queued(1));
+        assert_eq!(ids(player.upcoming()), ["a1", "b1", "a2"]);
+    }
+
+    #[test]
+    fn fair_position_without_requesters() {
+        let upcoming = vec![track("a1", 1000, Some("a")), track("n1", 1000, None), track("a2", 1000, Some("a"))];
+
+        assert_eq!(fair_position(&upcoming, None), 3);
+        assert_eq!(fair_position(&upcoming, Some(&TrackRequester::from("b".to_string()))), 2);
+        assert_eq!(fair_position(&[], None), 0);
+    }
+
+    #[test]
+    fn remove_upcoming_range_bounds() {
+        let mut player = playing(track("current", 1000, None));
+
+        for id in ["a", "b", "c", "d"].iter() {
+            player.queue_track(track(id, 1000, None), None).unwrap();
+        }
+
+        assert!(matches!(player.remove_upcoming_range(2..5), Err(AndelinkError::QueueIndexOutOfBounds)));
+        assert!(matches!(player.remove_upcoming_range(4..=4), Err(AndelinkError::QueueIndexOutOfBounds)));
+        assert!(matches!(player.remove_upcoming_range((Bound::Included(2), Bound::Excluded(1))), Err(AndelinkError::QueueIndexOutOfBounds)));
+
+        assert_eq!(ids(&player.remove_upcoming_range(1..3).unwrap()), ["b", "c"]);
+        assert_eq!(ids(&player.queue), ["current", "a", "d"]);
+
+        assert!(player.remove_upcoming_range(2..).unwrap().is_empty());
+        assert_eq!(ids(&player.remove_upcoming_range(..).unwrap()), ["a", "d"]);
+        assert_eq!(ids(&player.queue), ["current"]);
+    }
+
+    #[test]
+    fn limits_are_checked() {
+        let mut player = playing(track("current", 1000, Some("a")));
+        player.limits = QueueLimits::default()
+            .max_length(3)
+            .max_per_requester(2)
+            .max_track_length(Duration::from_secs(60))
+            .allow_streams(false);
+
+        player.queue_track(track("a1", 1000, Some("a")), None).unwrap();
+        player.queue_track(track("a2", 1000, Some("a")), None).unwrap();
+
+        assert!(matches!(player.queue_track(track("a3", 1000, Some("a")), None), Err(AndelinkError::RequesterQuotaExceeded(2))));
+        assert!(matches!(player.queue_track(track("long", 61_000, Some("b")), None), Err(AndelinkError::TrackTooLong(_))));
+        assert!(matches!(player.queue_track(stream("stream"), None), Err(AndelinkError::StreamsNotAllowed)));
+
+        player.queue_track(track("b1", 60_000, Some("b")), None).unwrap();
+
+        assert!(matches!(player.queue_track(track("b2", 1000, Some("b")), None), Err(AndelinkError::QueueFull(3))));
+    }
+
+    #[test]
+    fn limits_count_every_added_track() {
+        let player = Player {
+            limits: QueueLimits::default().max_per_requester(1),
+            ..Default::default()
+        };
+
+        let a1 = track("a1", 1000, Some("a"));
+        let a2 = track("a2", 1000, Some("a"));
+        let b1 = track("b1", 1000, Some("b"));
+
+        assert!(player.check_limits(vec![(&a1.track, a1.requester.as_ref()), (&b1.track, b1.requester.as_ref())]).is_ok());
+        assert!(matches!(
+            player.check_limits(vec![(&a1.track, a1.requester.as_ref()), (&a2.track, a2.requester.as_ref())]),
+            Err(AndelinkError::RequesterQuotaExceeded(1))
+        ));
+    }
+
+    #[test]
+    fn duplicate_policies() {
+        let mut player = playing(track("current", 1000, None));
+
+        player.queue_track(track("a", 1000, None), None).unwrap();
+        player.queue_track(track("b", 1000, None), None).unwrap();
+
+        player.duplicate_policy = DuplicatePolicy::Reject;
+
+        assert!(matches!(player.queue_track(track("current", 1000, None), None), Err(AndelinkError::DuplicateTrack)));
+        assert!(matches!(player.queue_track(track("a", 1000, None), Some(0)), Err(AndelinkError::DuplicateTrack)));
+
+        player.duplicate_policy = DuplicatePolicy::MoveToEnd;
+
+        assert_eq!(player.queue_track(track("a", 1000, None), None).unwrap(), QueueOutcome::Moved(1));
+        assert_eq!(ids(player.upcoming()), ["b", "a"]);
+
+        assert_eq!(player.queue_track(track("a", 1000, None), Some(0)).unwrap(), QueueOutcome::Moved(0));
+        assert_eq!(ids(player.upcoming()), ["a", "b"]);
+
+        player.duplicate_policy = DuplicatePolicy::Allow;
+
+        assert_eq!(player.queue_track(track("a", 1000, None), None).unwrap(), QueueOutcome::Queued(2));
+        assert!(matches!(player.queue_track(track("c", 1000, None), Some(4)), Err(AndelinkError::QueueIndexOutOfBounds)));
+    }
+
+    #[test]
+    fn time_until_skips_streams() {
+        let mut current = track("current", 10_000, None);
+
+        if let Some(info) = current.track.info.as_mut() {
+            info.position = 4000;
+        }
+
+        let mut player = playing(current);
+
+        player.queue.push(track("a", 3000, None));
+        player.queue.push(stream("stream"));
+        player.queue.push(track("b", 2000, None));
+
+        assert_eq!(player.time_until(0), Some(Duration::from_millis(6000)));
+        assert_eq!(player.time_until(1), Some(Duration::from_millis(9000)));
+        assert_eq!(player.time_until(2), Some(Duration::from_millis(9000)));
+        assert_eq!(player.time_until(3), None);
+        assert_eq!(player.queue_remaining(), Duration::from_millis(11_000));
+    }
+}
@@ -3,6 +3,7 @@ use serde::{
     Deserialize
 };
 use serenity::model::id::{UserId as DiscordUserId, ChannelId as DiscordChannelId};
+use std::time::Duration;
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
 #[serde(rename_all = "camelCase")]
@@ -19,6 +20,19 @@ pub struct Track {
     pub info: Option<TrackInfo>
 }
 
+impl Track {
+    /// Checks if both tracks point to the same song, comparing identifiers and uris if available.
+    pub fn is_same(&self, other: &Track) -> bool {
+        match (&self.info, &other.info) {
+            (Some(info), Some(other_info)) => {
+                info.identifier == other_info.identifier
+                    || (!info.uri.is_empty() && info.uri == other_info.uri)
+            },
+            _ => self.track == other.track
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct TrackInfo {
@@ -48,13 +62,50 @@ pub struct PlaylistInfo {
     pub selected_track: Option<i64>
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct QueuedTrack {
     pub track: Track,
     pub start_time: u64,
     pub end_time: Option<u64>,
     pub requester: Option<TrackRequester>,
-    pub channel: Option<DiscordChannelId>
+    pub channel: Option<DiscordChannelId>,
+    /// Time the volume takes to go from 0 to the volume of the player when the track starts
+    #[serde(default)]
+    pub fade_in: Option<Duration>,
+    /// Time before the end of the track the volume starts going down to 0
+    #[serde(default)]
+    pub fade_out: Option<Duration>
+}
+
+impl QueuedTrack {
+    /// Time the track plays for, between `start_time` and `end_time`.
+    ///
+    /// Returns `None` for streams and tracks without info.
+    pub fn duration(&self) -> Option<Duration> {
+        let (start, end) = self.bounds()?;
+
+        Some(Duration::from_millis(end - start))
+    }
+
+    /// Time left for the track to finish, using the position last reported by the server.
+    ///
+    /// Returns `None` for streams and tracks without info.
+    pub fn remaining(&self) -> Option<Duration> {
+        let (start, end) = self.bounds()?;
+        let position = self.track.info.as_ref().map(|info| info.position).unwrap_or(0);
+
+        Some(Duration::from_millis(end.saturating_sub(position.max(start))))
+    }
+
+    /// Start and end of the played part of the track in milliseconds.
+    fn bounds(&self) -> Option<(u64, u64)> {
+        let info = self.track.info.as_ref().filter(|info| !info.is_stream)?;
+
+        let end = self.end_time.map(|end| end.min(info.length)).unwrap_or(info.length);
+        let start = self.start_time.min(end);
+
+        Some((start, end))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -72,32 +123,100 @@ impl std::fmt::Display for TrackSearch<'_> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TrackRequester {
     pub id: Option<DiscordUserId>,
-    pub name: Option<String>
+    pub name: Option<String>,
+    /// Whether the track was queued by the autoplay of the player instead of a user
+    #[serde(default)]
+    pub autoplay: bool
+}
+
+impl TrackRequester {
+    /// Requester of the tracks queued by the autoplay of a player.
+    pub fn autoplay() -> TrackRequester {
+        TrackRequester{id: None, name: Some("Autoplay".to_string()), autoplay: true}
+    }
+
+    /// Checks if both requesters are the same user, comparing ids if both have one and names otherwise.
+    pub fn is_same(&self, other: &TrackRequester) -> bool {
+        match (self.id, other.id) {
+            (Some(id), Some(other_id)) => id == other_id,
+            _ => self.name.is_some() && self.name == other.name
+        }
+    }
 }
 
 impl From<DiscordUserId> for TrackRequester {
     fn from(id: DiscordUserId) -> TrackRequester {
-        TrackRequester{id: Some(id), name: None}
+        TrackRequester{id: Some(id), name: None, autoplay: false}
     }
 }
 
 impl From<String> for TrackRequester {
     fn from(name: String) -> TrackRequester {
-        TrackRequester{id: None, name: Some(name)}
+        TrackRequester{id: None, name: Some(name), autoplay: false}
     }
 }
 
 impl From<(DiscordUserId, String)> for TrackRequester {
     fn from(data: (DiscordUserId, String)) -> TrackRequester {
-        TrackRequester {id: Some(data.0), name: Some(data.1)}
+        TrackRequester {id: Some(data.0), name: Some(data.1), autoplay: false}
     }
 }
 
 impl From<(u64, String)> for TrackRequester {
     fn from(data: (u64, String)) -> TrackRequester {
-        TrackRequester{id: Some(DiscordUserId::from(data.0)), name: Some(data.1)}
+        TrackRequester{id: Some(DiscordUserId::from(data.0)), name: Some(data.1), autoplay: false}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queued(length: u64, position: u64, start_time: u64, end_time: Option<u64>) -> QueuedTrack {
+        QueuedTrack {
+            track: Track {
+                track: "track".to_string(),
+                info: Some(TrackInfo {
+                    length,
+                    position,
+                    ..Default::default()
+                })
+            },
+            start_time,
+            end_time,
+            ..Default::default()
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn remaining_uses_position() {
+        let track = queued(10_000, 4000, 0, None);
+
+        assert_eq!(track.duration(), Some(Duration::from_secs(10)));
+        assert_eq!(track.remaining(), Some(Duration::from_millis(6000)));
+    }
+
+    #[test]
+    fn remaining_is_bounded_by_start_and_end() {
+        assert_eq!(queued(10_000, 1000, 5000, None).remaining(), Some(Duration::from_millis(5000)));
+        assert_eq!(queued(10_000, 4000, 0, Some(8000)).remaining(), Some(Duration::from_millis(4000)));
+        assert_eq!(queued(10_000, 9000, 0, Some(8000)).remaining(), Some(Duration::from_millis(0)));
+        assert_eq!(queued(10_000, 0, 0, Some(20_000)).duration(), Some(Duration::from_secs(10)));
+        assert_eq!(queued(10_000, 0, 12_000, None).duration(), Some(Duration::from_millis(0)));
+    }
+
+    #[test]
+    fn streams_have_no_remaining_time() {
+        let mut track = queued(0, 4000, 0, None);
+
+        if let Some(info) = track.track.info.as_mut() {
+            info.is_stream = true;
+        }
+
+        assert_eq!(track.remaining(), None);
+        assert_eq!(QueuedTrack::default().remaining(), None);
+    }
+}
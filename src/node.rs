@@ -4,19 +4,24 @@ use serenity::model::id::{
     UserId as DiscordUserId,
     GuildId as DiscordGuildId
 };
-use crate::{builder::NodeBuilder, cluster::Cluster, error::{AndelinkError, AndelinkResult}, model::{gateway::{GatewayEvent, TrackStart, TrackFinish, WebSocketClosed, Stats}, play_parameters::PlayParameters, half_update::HalfVoiceUpdate, player::Player, track::{Track, Tracks, QueuedTrack}}, types::WebSocketConnection};
+use crate::{builder::NodeBuilder, cluster::Cluster, error::{AndelinkError, AndelinkResult}, model::{Codes, NodeCodes, events::Filters, filters::{self, FiltersBuilder}, gateway::{GatewayEvent, TrackStart, TrackFinish, TrackStuck, TrackException, WebSocketClosed, Stats}, play_parameters::PlayParameters, half_update::HalfVoiceUpdate, player::{Player, LoopMode, QueueLimits, DuplicatePolicy, QueueOutcome}, track::{Track, Tracks, QueuedTrack, TrackRequester}, library_events::{PlayerDropped, NodeConnected, NodeDisconnected, NodeReconnecting, TrackQueued, TrackAdvanced, QueueFinished}}, types::WebSocketConnection, reconnect::ReconnectPolicy, autoplay::AutoplayProvider, events::{process, EventType}};
 use std::{
-    collections::HashMap,
-    sync::Arc,
-    time::Duration
+    collections::VecDeque,
+    ops::RangeBounds,
+    sync::{Arc, atomic::{AtomicBool, Ordering}},
+    time::{Duration, Instant}
 };
-use tokio::sync::RwLock;
+use tokio::{sync::{RwLock, Notify, mpsc::{self, UnboundedSender, UnboundedReceiver}}, task::JoinHandle};
+use parking_lot::{Mutex, RwLock as SyncRwLock};
 use typemap_rev::TypeMap;
 use dashmap::DashMap;
-use futures::StreamExt;
+use futures::{StreamExt, SinkExt};
 use tracing::{info, error, warn};
 use http::Request;
-use tokio_tungstenite::tungstenite::Message as TungsteniteMessage;
+use tokio_tungstenite::tungstenite::{
+    Message as TungsteniteMessage,
+    protocol::{CloseFrame, frame::coding::CloseCode}
+};
 use regex::Regex;
 use songbird::ConnectionInfo;
 
@@ -25,34 +30,63 @@ lazy_static!(
     static ref URL_REGEX: Regex = Regex::new(r"https?://(?:www\.)?.+").unwrap();
 );
 
+/// Time between the volume changes of a fade.
+const FADE_STEP: Duration = Duration::from_millis(250);
+
+/// Task changing the volume of a player over time.
+struct Fade {
+    handle: JoinHandle<()>,
+    /// Whether it's the fade out of the current track
+    fade_out: bool
+}
+
 pub struct UniversalNode {
-    inner: RwLock<NodeInner>,
+    inner: NodeInner,
     http: HttpClient,
-    rest_url: String
+    rest_url: String,
+    shutting_down: AtomicBool,
+    shutdown_notify: Notify,
+    task: Mutex<Option<JoinHandle<()>>>
 }
 
 impl std::ops::Deref for UniversalNode {
-    type Target = RwLock<NodeInner>;
+    type Target = NodeInner;
 
     fn deref(&self) -> &Self::Target {
         &self.inner
     }
 }
 
+/// State of a node.
+///
+/// Every piece of mutable state has its own lock, players are stored per guild so commands on different guilds never
+/// contend, and payloads are handed to a dedicated writer task that owns the websocket sink.
 pub struct NodeInner {
     pub rest: String,
     pub socket: String,
     pub pass: String,
     pub shards: u64,
     pub id: DiscordUserId,
-    pub socket_write: Option<WebSocketConnection>,
     pub http: HttpClient,
-    pub players: HashMap<u64, Player>,
-    pub stats: Option<Stats>,
+    pub players: DashMap<u64, Player>,
+    pub stats: SyncRwLock<Option<Stats>>,
     pub data: Arc<RwLock<TypeMap>>,
     pub node_id: u8,
     pub cluster: Arc<Cluster>,
     pub waiting: DashMap<DiscordGuildId, HalfVoiceUpdate>,
+    pub resume_key: Option<String>,
+    pub resume_timeout: u64,
+    pub regions: Vec<String>,
+    pub reconnect_policy: Option<ReconnectPolicy>,
+    writer: Mutex<Option<UnboundedSender<TungsteniteMessage>>>,
+    outbox: Mutex<VecDeque<(Instant, TungsteniteMessage)>>,
+    outbox_size: usize,
+    outbox_expiry: Duration,
+    reconnecting: AtomicBool,
+    resumable: AtomicBool,
+    fades: DashMap<u64, Fade>,
+    /// Timers starting the fade out of the current track of each player
+    fade_out_timers: DashMap<u64, JoinHandle<()>>,
 }
 
 impl NodeInner {
@@ -70,29 +104,143 @@ impl NodeInner {
             pass: builder.pass,
             shards: builder.shards,
             id: builder.id.unwrap().into(),
-            socket_write: None,
-            players: Default::default(),
+            players: DashMap::new(),
             http,
-            stats: None,
+            stats: SyncRwLock::new(None),
             data: Arc::clone(&cluster.shared_data),
             cluster,
             node_id: builder.node_id.unwrap(),
             waiting: DashMap::new(),
+            resume_key: builder.resume_key,
+            resume_timeout: builder.resume_timeout,
+            regions: builder.regions,
+            reconnect_policy: builder.reconnect_policy,
+            writer: Mutex::new(None),
+            outbox: Mutex::new(VecDeque::new()),
+            outbox_size: builder.outbox_size,
+            outbox_expiry: builder.outbox_expiry,
+            reconnecting: AtomicBool::new(false),
+            resumable: AtomicBool::new(false),
+            fades: DashMap::new(),
+            fade_out_timers: DashMap::new(),
         }
     }
 
     fn get_ws_request(&self) -> Request<()> {
-        Request::builder()
+        let mut request = Request::builder()
             .uri(&self.socket)
             .header("Authorization", &self.pass)
             .header("Num-Shards", &self.shards.to_string())
-            .header("User-Id", &self.id.to_string())
-            .body(())
-            .unwrap()
+            .header("User-Id", &self.id.to_string());
+
+        // Only ask for a resume if the server already knows about our key
+        if let (Some(key), true) = (&self.resume_key, self.resumable.load(Ordering::SeqCst)) {
+            request = request.header("Resume-Key", key);
+        }
+
+        request.body(()).unwrap()
+    }
+
+    /// Returns whether the node has a websocket connection to send payloads through.
+    pub fn is_connected(&self) -> bool {
+        self.writer.lock().is_some()
+    }
+
+    /// Hands a message to the writer task of the node, or buffers it if the node is reconnecting.
+    ///
+    /// The channel is unbounded on purpose: payloads are a few hundred bytes and a single node only gets a
+    /// handful per guild and track, so the writer never falls behind in practice, and a bounded channel would
+    /// force every command to be async just to wait for room. The trade off is that there's no backpressure and
+    /// an `Ok` only means the payload was queued, errors writing it to the socket are logged by the writer task.
+    pub(crate) fn send_message(&self, message: TungsteniteMessage) -> AndelinkResult<()> {
+        match &*self.writer.lock() {
+            Some(sender) => sender.send(message).map_err(|_| AndelinkError::NoWebsocket),
+            None => self.buffer_message(message)
+        }
+    }
+
+    fn buffer_message(&self, message: TungsteniteMessage) -> AndelinkResult<()> {
+        if self.outbox_size == 0 || !self.reconnecting.load(Ordering::SeqCst) {
+            return Err(AndelinkError::NoWebsocket);
+        }
+
+        let mut outbox = self.outbox.lock();
+
+        Self::purge_expired(&mut outbox, self.outbox_expiry);
+
+        if outbox.len() >= self.outbox_size {
+            return Err(AndelinkError::OutboxFull);
+        }
+
+        outbox.push_back((Instant::now(), message));
+
+        Ok(())
+    }
+
+    fn purge_expired(outbox: &mut VecDeque<(Instant, TungsteniteMessage)>, expiry: Duration) {
+        while outbox.front().map(|(at, _)| at.elapsed() > expiry).unwrap_or(false) {
+            outbox.pop_front();
+        }
     }
 
-    pub(crate) async fn play_next(&mut self, guild_id: u64) -> AndelinkResult<()> {
-        return if let Some(player) = self.players.get_mut(&guild_id) {
+    /// Sends every buffered message that didn't expire, in the same order they were buffered.
+    fn flush_outbox(&self, sender: &UnboundedSender<TungsteniteMessage>) {
+        let mut outbox = self.outbox.lock();
+
+        Self::purge_expired(&mut outbox, self.outbox_expiry);
+
+        if !outbox.is_empty() {
+            info!("Node id {} flushing {} buffered payloads", self.node_id, outbox.len());
+        }
+
+        for (_, message) in outbox.drain(..) {
+            let _ = sender.send(message);
+        }
+    }
+
+    fn clear_outbox(&self) {
+        let mut outbox = self.outbox.lock();
+
+        if !outbox.is_empty() {
+            warn!("Node id {} discarding {} buffered payloads", self.node_id, outbox.len());
+        }
+
+        outbox.clear();
+    }
+
+    /// Sends a payload for the given guild.
+    pub(crate) fn send(&self, guild_id: impl Into<DiscordGuildId>, code: Codes) -> AndelinkResult<()> {
+        self.send_message(code.to_message(guild_id))
+    }
+
+    fn configure_resuming(&self) -> AndelinkResult<()> {
+        let key = if let Some(key) = &self.resume_key { key.clone() } else { return Ok(()) };
+
+        let payload = crate::model::events::ConfigureResuming {
+            key,
+            timeout: self.resume_timeout,
+        };
+
+        self.send_message(NodeCodes::ConfigureResuming(payload).to_message())?;
+
+        self.resumable.store(true, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    pub(crate) fn play_next(&self, guild_id: u64, previous: Option<QueuedTrack>) -> AndelinkResult<()> {
+        // Fades of the previous track don't apply anymore
+        self.cancel_fade(guild_id);
+        self.cancel_fade_out_timer(guild_id);
+
+        let (payload, track, fade_in, restore) = if let Some(mut player) = self.players.get_mut(&guild_id) {
+            // The track may have played before, like when looping it
+            let start_time = player.queue[0].start_time;
+
+            if let Some(info) = player.queue[0].track.info.as_mut() {
+                info.position = start_time;
+            }
+
             let track = player.queue[0].clone();
 
             player.now_playing = Some(player.queue[0].clone());
@@ -104,27 +252,363 @@ impl NodeInner {
                 end_time: track.end_time,
             };
 
-            if let Some(ref mut socket) = self.socket_write {
-                crate::model::Codes::Play(payload).send(guild_id, socket).await?;
-            } else {
-                return Err(AndelinkError::NoWebsocket);
+            let base = player.fade_base.take();
+
+            let fade_in = track.fade_in.map(|duration| {
+                let target = base.unwrap_or(player.volume);
+
+                player.fade_base = Some(target);
+
+                (target, duration)
+            });
+
+            let restore = if fade_in.is_none() { base } else { None };
+
+            (payload, track, fade_in, restore)
+        } else {
+            return Err(AndelinkError::PlayerNotFound);
+        };
+
+        if let Some(volume) = restore {
+            // The previous track faded out
+            self.send_volume(guild_id, volume)?;
+        }
+
+        if fade_in.is_some() {
+            self.send_volume(guild_id, 0)?;
+        }
+
+        self.send(guild_id, Codes::Play(payload))?;
+
+        if let Some((target, duration)) = fade_in {
+            self.spawn_fade(guild_id, 0, target, duration, false);
+        }
+
+        self.schedule_fade_out(guild_id);
+
+        self.dispatch(EventType::TrackAdvanced(TrackAdvanced {
+            guild_id,
+            previous,
+            current: track,
+        }));
+
+        Ok(())
+    }
+
+    /// Plays a track right away, out of the queue order, making it the current track of the player.
+    ///
+    /// If something is playing and `replace` isn't set, the track is sent with `noReplace` and the local state is kept,
+    /// the server only plays it if it wasn't playing anything.
+    pub(crate) fn play_now(&self, guild_id: u64, track: QueuedTrack, replace: bool) -> AndelinkResult<()> {
+        let previous = self.with_player(guild_id, |player| {
+            if player.now_playing.is_some() && !replace {
+                return Ok(None);
             }
 
-            Ok(())
+            // The current track is the first one of the queue, replace it instead of keeping it as upcoming
+            let previous = match player.now_playing.take() {
+                Some(previous) if !player.queue.is_empty() => {
+                    player.queue[0] = track.clone();
+                    player.push_history(previous.clone());
+
+                    Some(previous)
+                },
+                _ => {
+                    player.queue.insert(0, track.clone());
+
+                    None
+                }
+            };
+
+            Ok(Some(previous))
+        })?;
+
+        match previous {
+            Some(previous) => self.play_next(guild_id, previous),
+            None => {
+                let payload = crate::model::events::Play {
+                    track: track.track.track,
+                    no_replace: true,
+                    start_time: track.start_time,
+                    end_time: track.end_time,
+                };
+
+                self.send(guild_id, Codes::Play(payload))
+            }
+        }
+    }
+
+    /// Returns the shared reference of the node kept by the cluster, if it wasn't removed from it.
+    fn shared(&self) -> Option<Arc<UniversalNode>> {
+        self.cluster.registry.get(&self.node_id).map(|node| Arc::clone(node.value()))
+    }
+
+    /// Dispatches a library generated event to the event handler of the cluster.
+    pub(crate) fn dispatch(&self, event: EventType) {
+        let node = match self.shared() {
+            Some(node) => node,
+            None => return
+        };
+
+        process(node, Arc::clone(&self.cluster.event_handler), event);
+    }
+
+    /// Sends the volume op and keeps the volume on the player.
+    fn send_volume(&self, guild_id: u64, volume: u16) -> AndelinkResult<()> {
+        let payload = crate::model::events::Volume {
+            volume,
+        };
+
+        self.send(guild_id, Codes::Volume(payload))?;
+
+        if let Some(mut player) = self.players.get_mut(&guild_id) {
+            player.volume = volume;
+        }
+
+        Ok(())
+    }
+
+    /// Stops the fade running on the player, if any.
+    fn cancel_fade(&self, guild_id: u64) {
+        if let Some((_, fade)) = self.fades.remove(&guild_id) {
+            fade.handle.abort();
+        }
+    }
+
+    /// Stops the timer that starts the fade out of the current track, if any.
+    fn cancel_fade_out_timer(&self, guild_id: u64) {
+        if let Some((_, timer)) = self.fade_out_timers.remove(&guild_id) {
+            timer.abort();
+        }
+    }
+
+    /// Stops the fades, and the timers starting them, of every player of the node.
+    pub(crate) fn cancel_fades(&self) {
+        self.fades.retain(|_, fade| {
+            fade.handle.abort();
+            false
+        });
+
+        self.fade_out_timers.retain(|_, timer| {
+            timer.abort();
+            false
+        });
+    }
+
+    /// Stops the fade running on the player, if any, and sets back the volume it had before fading.
+    fn reset_fade(&self, guild_id: u64) -> AndelinkResult<()> {
+        self.cancel_fade(guild_id);
+        self.cancel_fade_out_timer(guild_id);
+
+        let base = self.players.get_mut(&guild_id).and_then(|mut player| player.fade_base.take());
+
+        match base {
+            Some(volume) => self.send_volume(guild_id, volume),
+            None => Ok(())
+        }
+    }
+
+    /// Same as `reset_fade`, but only if the current track is fading out. The fade out timer is always stopped.
+    fn reset_fade_out(&self, guild_id: u64) -> AndelinkResult<()> {
+        self.cancel_fade_out_timer(guild_id);
+
+        if self.fades.get(&guild_id).map_or(false, |fade| fade.fade_out) {
+            self.reset_fade(guild_id)
         } else {
-            Err(AndelinkError::PlayerNotFound)
+            Ok(())
         }
     }
 
-    async fn create_session(&mut self, guild_id: impl Into<DiscordGuildId>, conn_info: &ConnectionInfo) -> AndelinkResult<()> {
-        let guild_id = guild_id.into();
+    /// Starts a task that changes the volume of the player step by step, replacing the fade it had before.
+    fn spawn_fade(&self, guild_id: u64, from: u16, to: u16, duration: Duration, fade_out: bool) {
+        let node = match self.shared() {
+            Some(node) => node,
+            None => return
+        };
+
+        let steps = std::cmp::max(1, duration.as_millis() / FADE_STEP.as_millis()) as u32;
+        let step = duration / steps;
+
+        let handle = tokio::spawn(async move {
+            for i in 1..=steps {
+                tokio::time::sleep(step).await;
+
+                let volume = from as i64 + (to as i64 - from as i64) * i as i64 / steps as i64;
+
+                if let Err(why) = node.send_volume(guild_id, volume as u16) {
+                    warn!("Stopping volume fade on guild id {}, error: {}", guild_id, why.to_string());
+                    break;
+                }
+            }
+        });
+
+        if let Some(previous) = self.fades.insert(guild_id, Fade { handle, fade_out }) {
+            previous.handle.abort();
+        }
+    }
+
+    /// Schedules the fade out of the current track so it starts `fade_out` before the end of the track,
+    /// using the last known position, or starts it right away if the track is already that close to its end.
+    ///
+    /// Called again on every position update of the server to correct the timer.
+    fn schedule_fade_out(&self, guild_id: u64) {
+        // A fade out that already started isn't restarted
+        if self.fades.get(&guild_id).map_or(false, |fade| fade.fade_out) {
+            return;
+        }
+
+        let (fade_out, remaining) = match self.players.get(&guild_id) {
+            Some(player) if !player.paused => {
+                let fade_out = player.now_playing.as_ref().and_then(|current| current.fade_out);
+
+                match (fade_out, player.current_remaining()) {
+                    (Some(fade_out), Some(remaining)) => (fade_out, remaining),
+                    _ => return
+                }
+            },
+            _ => return
+        };
+
+        if remaining <= fade_out {
+            self.cancel_fade_out_timer(guild_id);
+            self.start_fade_out(guild_id, remaining);
+
+            return;
+        }
 
-        let socket = if let Some(s) = &mut self.socket_write {
-            s
+        let node = match self.shared() {
+            Some(node) => node,
+            None => return
+        };
+
+        let timer = tokio::spawn(async move {
+            tokio::time::sleep(remaining - fade_out).await;
+
+            node.start_fade_out(guild_id, fade_out);
+        });
+
+        if let Some(previous) = self.fade_out_timers.insert(guild_id, timer) {
+            previous.abort();
+        }
+    }
+
+    /// Fades the volume of the current track down to 0 over the given time.
+    fn start_fade_out(&self, guild_id: u64, duration: Duration) {
+        let from = match self.players.get_mut(&guild_id) {
+            Some(mut player) if !player.paused => {
+                if player.fade_base.is_none() {
+                    player.fade_base = Some(player.volume);
+                }
+
+                player.volume
+            },
+            _ => return
+        };
+
+        self.spawn_fade(guild_id, from, 0, duration, true);
+    }
+
+    /// Changes the volume of the player to the given one over time, replacing the fade it had before.
+    pub(crate) fn fade_volume(&self, guild_id: u64, volume: u16, duration: Duration) -> AndelinkResult<()> {
+        self.cancel_fade(guild_id);
+
+        let from = self.with_player(guild_id, |player| {
+            player.fade_base = None;
+            Ok(player.volume)
+        })?;
+
+        self.spawn_fade(guild_id, from, std::cmp::min(volume, 1000), duration, false);
+
+        Ok(())
+    }
+
+    /// Removes the current track from the queue following the loop mode of the player,
+    /// and starts playing the next one, if any. Failed tracks are always removed from the queue.
+    ///
+    /// Returns `true` if a new track was started.
+    pub(crate) fn advance_queue(&self, guild_id: u64, failed: bool) -> AndelinkResult<bool> {
+        let (should_play_next, previous) = if let Some(mut player) = self.players.get_mut(&guild_id) {
+            let previous = player.now_playing.take();
+
+            // Remove track from queue
+            if !player.queue.is_empty() {
+                let finished = player.queue.remove(0);
+
+                // Put it back depending on the loop mode
+                match player.loop_mode {
+                    LoopMode::Track if !failed => player.queue.insert(0, finished),
+                    LoopMode::Queue if !failed => {
+                        player.push_history(finished.clone());
+                        player.queue.push(finished);
+                    },
+                    _ if !failed => player.push_history(finished),
+                    _ => ()
+                }
+            }
+
+            // Check if we should play next track
+            (!player.queue.is_empty(), previous)
         } else {
-            return Err(AndelinkError::NoWebsocket);
+            return Err(AndelinkError::PlayerNotFound);
+        };
+
+        if should_play_next {
+            self.play_next(guild_id, previous)?;
+        } else {
+            // Don't leave the player muted by the fade out of the last track
+            self.reset_fade(guild_id)?;
+
+            self.dispatch(EventType::QueueFinished(QueueFinished {
+                guild_id,
+                last: previous,
+            }));
+        }
+
+        Ok(should_play_next)
+    }
+
+    /// Runs the given function with the player of the given guild locked.
+    pub(crate) fn with_player<T, F>(&self, guild_id: u64, func: F) -> AndelinkResult<T>
+    where
+        F: FnOnce(&mut Player) -> AndelinkResult<T>
+    {
+        match self.players.get_mut(&guild_id) {
+            Some(mut player) => func(player.value_mut()),
+            None => Err(AndelinkError::PlayerNotFound)
+        }
+    }
+
+    /// Adds a track to the queue of a player following its duplicate policy and limits, starting it if nothing is playing.
+    ///
+    /// The track goes to the given position of the upcoming tracks, or to the end of the queue if there is none.
+    pub(crate) fn queue_track(&self, guild_id: u64, track: QueuedTrack, position: Option<usize>) -> AndelinkResult<QueueOutcome> {
+        let queued = track.clone();
+
+        let outcome = self.with_player(guild_id, |player| player.queue_track(track, position))?;
+
+        let position = match outcome {
+            QueueOutcome::Started => None,
+            QueueOutcome::Queued(position) => Some(position),
+            // Nothing was added
+            QueueOutcome::Moved(_) => return Ok(outcome)
         };
 
+        self.dispatch(EventType::TrackQueued(TrackQueued {
+            guild_id,
+            track: queued,
+            position,
+        }));
+
+        if outcome == QueueOutcome::Started {
+            self.play_next(guild_id, None)?;
+        }
+
+        Ok(outcome)
+    }
+
+    fn create_session(&self, guild_id: impl Into<DiscordGuildId>, conn_info: &ConnectionInfo) -> AndelinkResult<()> {
+        let guild_id = guild_id.into();
+
         let token = if conn_info.token.is_empty() { return Err(AndelinkError::MissingHandlerToken); } else { conn_info.token.clone() };
 
         let endpoint = if conn_info.endpoint.is_empty() { return Err(AndelinkError::MissingHandlerEndpoint); } else { conn_info.endpoint.clone() };
@@ -142,111 +626,320 @@ impl NodeInner {
             event
         };
 
-        self.players.insert(guild_id.clone().0, Player::default());
+        // Recreate the player saved before a restart, if any
+        if let Some((_, snapshot)) = self.cluster.pending_restores.remove(&guild_id.0) {
+            let mut player = snapshot.clone().into_player();
 
-        crate::model::Codes::VoiceUpdate(payload).send(guild_id, socket).await
-    }
+            player.voice_update = Some(payload);
 
-    async fn destroy(&mut self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<()> {
-        let guild_id = guild_id.into();
+            info!("Restoring saved player of guild id {} on node id {}", guild_id.0, self.node_id);
 
-        let socket = if let Some(x) = &mut self.socket_write { x } else {
-            return Err(AndelinkError::NoWebsocket);
+            let result = self.restore_player(guild_id.0, player);
+
+            // Keep the snapshot so the next session of the guild can try again
+            if result.is_err() {
+                self.cluster.pending_restores.insert(guild_id.0, snapshot);
+            }
+
+            return result;
+        }
+
+        let player = Player {
+            guild: guild_id,
+            voice_update: Some(payload.clone()),
+            ..Default::default()
         };
 
-        let _ = self.players.remove(&guild_id.0);
+        // Buffered while reconnecting, the player is only kept if the voice update will be sent
+        self.send(guild_id, Codes::VoiceUpdate(payload))?;
 
-        crate::model::Codes::Destroy.send(guild_id, socket).await?;
+        self.players.insert(guild_id.0, player);
 
         Ok(())
     }
 
-    async fn stop(&mut self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<()> {
-        let socket = if let Some(x) = &mut self.socket_write { x } else {
-            return Err(AndelinkError::NoWebsocket);
+    /// Sends everything needed to recreate the state of a player on the server, then inserts it.
+    ///
+    /// The player is only inserted if every payload was handed to the connection.
+    pub(crate) fn restore_player(&self, guild_id: u64, mut player: Player) -> AndelinkResult<()> {
+        let voice_update = if let Some(v) = player.voice_update.clone() { v } else {
+            return Err(AndelinkError::InvalidDataToVoiceUpdate);
         };
 
-        crate::model::Codes::Stop.send(guild_id, socket).await?;
+        // Fades aren't carried over, so the player gets the volume it had before fading
+        self.cancel_fade(guild_id);
+        self.cancel_fade_out_timer(guild_id);
+
+        if let Some(volume) = player.fade_base.take() {
+            player.volume = volume;
+        }
+
+        self.send(guild_id, Codes::VoiceUpdate(voice_update))?;
+
+        if player.volume != 100 {
+            let payload = crate::model::events::Volume {
+                volume: player.volume,
+            };
+
+            self.send(guild_id, Codes::Volume(payload))?;
+        }
+
+        if player.filters != Filters::default() {
+            self.send(guild_id, Codes::Filters(filters::payload(&player.filters, &player.equalizer)))?;
+        } else if player.equalizer.iter().any(|gain| *gain != 0.) {
+            let bands = filters::bands(&player.equalizer);
+
+            self.send(guild_id, Codes::Equalize(crate::model::events::Equalize { bands }))?;
+        }
+
+        if let Some(current) = &player.now_playing {
+            // Continue the track where the previous node left it
+            let position = current.track.info.as_ref().map(|info| info.position).unwrap_or(0);
+
+            let payload = crate::model::events::Play {
+                track: current.track.track.clone(),
+                no_replace: false,
+                start_time: std::cmp::max(position, current.start_time),
+                end_time: current.end_time,
+            };
+
+            self.send(guild_id, Codes::Play(payload))?;
+        }
+
+        if player.paused {
+            let payload = crate::model::events::Pause {
+                pause: true,
+            };
+
+            self.send(guild_id, Codes::Pause(payload))?;
+        }
+
+        self.players.insert(guild_id, player);
+
+        self.schedule_fade_out(guild_id);
+
+        Ok(())
+    }
+
+    /// Destroys every player on the server, keeping the local state so they can be migrated.
+    pub(crate) fn destroy_server_players(&self) -> AndelinkResult<()> {
+        let guilds = self.players.iter().map(|item| *item.key()).collect::<Vec<_>>();
+
+        for guild_id in guilds {
+            self.send(guild_id, Codes::Destroy)?;
+        }
 
         Ok(())
     }
 
-    async fn skip(&mut self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<Option<QueuedTrack>> {
+    /// Sends a close frame to the server, the connection is dropped once the writer task sends it.
+    fn close(&self) {
+        let frame = CloseFrame {
+            code: CloseCode::Normal,
+            reason: "Node removed from cluster".into()
+        };
+
+        if let Err(why) = self.send_message(TungsteniteMessage::Close(Some(frame))) {
+            warn!("Node id {} failed to send close frame, error: {}", self.node_id, why.to_string());
+        }
+    }
+
+    fn destroy(&self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<()> {
+        let guild_id = guild_id.into();
+
+        // Remove it first so a reconnection never recreates it, the destroy op is buffered like any other command
+        let _ = self.players.remove(&guild_id.0);
+
+        self.cancel_fade(guild_id.0);
+        self.cancel_fade_out_timer(guild_id.0);
+
+        self.send(guild_id, Codes::Destroy)
+    }
+
+    /// Stops the current track, keeping the queue as it is so `skip` still moves to the next track.
+    fn stop(&self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<()> {
+        let guild_id = guild_id.into();
+
+        self.send_stop(guild_id)?;
+
+        // Don't leave the player muted if the track was fading
+        self.reset_fade(guild_id.0)
+    }
+
+    fn send_stop(&self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<()> {
+        self.send(guild_id, Codes::Stop)
+    }
+
+    fn skip(&self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<Option<QueuedTrack>> {
         let guild_id = guild_id.into();
 
-        return if let Some(player) = self.players.get_mut(&guild_id.0) {
-            player.now_playing = None;
+        let (return_value, should_play_next, previous) = if let Some(mut player) = self.players.get_mut(&guild_id.0) {
+            let previous = player.now_playing.take();
+
+            if player.queue.len() == 0 {
+                return Ok(None);
+            }
+
+            let return_value = player.queue.remove(0);
+
+            player.push_history(return_value.clone());
+
+            // Skipped tracks still go back to the queue when looping the whole queue
+            if player.loop_mode == LoopMode::Queue {
+                player.queue.push(return_value.clone());
+            }
+
+            (return_value, player.queue.len() >= 1, previous)
+        } else {
+            return Err(AndelinkError::PlayerNotFound);
+        };
+
+        if should_play_next {
+            self.play_next(guild_id.0, previous)?;
+        } else {
+            self.send_stop(guild_id)?;
+            self.reset_fade(guild_id.0)?;
+
+            self.dispatch(EventType::QueueFinished(QueueFinished {
+                guild_id: guild_id.0,
+                last: previous,
+            }));
+        }
+
+        Ok(Some(return_value))
+    }
+
+    /// Takes the last track of the history and plays it, the current track becoming the next one.
+    fn previous(&self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<Option<QueuedTrack>> {
+        let guild_id = guild_id.into();
+
+        let (previous, current) = if let Some(mut player) = self.players.get_mut(&guild_id.0) {
+            let previous = if let Some(track) = player.history.pop_back() { track } else {
+                return Ok(None);
+            };
+
+            player.queue.insert(0, previous.clone());
+
+            (previous, player.now_playing.take())
+        } else {
+            return Err(AndelinkError::PlayerNotFound);
+        };
+
+        self.play_next(guild_id.0, current)?;
+
+        Ok(Some(previous))
+    }
+
+    fn set_pause(&self, guild_id: impl Into<DiscordGuildId>, pause: bool) -> AndelinkResult<()> {
+        let guild_id = guild_id.into();
+
+        // The fade out starts again from the restored volume when resuming
+        self.reset_fade_out(guild_id.0)?;
+
+        let payload = crate::model::events::Pause {
+            pause,
+        };
 
-            return if player.queue.len() == 0 {
-                Ok(None)
-            } else if player.queue.len() == 1 {
-                let return_value = player.queue.remove(0);
+        self.send(guild_id, Codes::Pause(payload))?;
 
-                self.stop(guild_id).await?;
+        if let Some(mut player) = self.players.get_mut(&guild_id.0) {
+            player.paused = pause;
+        }
 
-                Ok(Some(return_value))
-            } else {
-                let return_value = player.queue.remove(0);
+        if !pause {
+            self.schedule_fade_out(guild_id.0);
+        }
 
-                self.play_next(guild_id.0).await?;
+        Ok(())
+    }
 
-                Ok(Some(return_value))
-            }
-        } else {
-            Err(AndelinkError::PlayerNotFound)
+    /// Checks that the current track of the player can be seeked, returning its position and the part of it
+    /// that is played, `start_time` to `end_time`, if they are known.
+    fn seekable(&self, guild_id: u64) -> AndelinkResult<Option<(u64, u64, u64)>> {
+        match self.players.get(&guild_id) {
+            Some(player) => match &player.now_playing {
+                Some(current) => match &current.track.info {
+                    Some(info) if info.is_stream || !info.is_seekable => Err(AndelinkError::TrackNotSeekable),
+                    Some(info) => {
+                        let end = current.end_time.map_or(info.length, |end| std::cmp::min(end, info.length));
+                        let start = std::cmp::min(current.start_time, end);
+
+                        Ok(Some((info.position, start, end)))
+                    },
+                    None => Ok(None)
+                },
+                None => Err(AndelinkError::NothingPlaying)
+            },
+            None => Err(AndelinkError::PlayerNotFound)
         }
     }
 
-    async fn set_pause(&mut self, guild_id: impl Into<DiscordGuildId>, pause: bool) -> AndelinkResult<()> {
-        let socket = if let Some(x) = &mut self.socket_write { x } else {
-            return Err(AndelinkError::NoWebsocket);
-        };
+    fn seek(&self, guild_id: impl Into<DiscordGuildId>, time: Duration) -> AndelinkResult<()> {
+        let guild_id = guild_id.into().0;
+        let position = time.as_millis() as u64;
 
-        let payload = crate::model::events::Pause {
-            pause,
+        let position = match self.seekable(guild_id)? {
+            Some((_, _, end)) => std::cmp::min(position, end),
+            None => position
         };
 
-        crate::model::Codes::Pause(payload).send(guild_id, socket).await?;
+        self.send_seek(guild_id, position)
+    }
 
-        Ok(())
+    fn seek_by(&self, guild_id: impl Into<DiscordGuildId>, offset_ms: i64) -> AndelinkResult<()> {
+        let guild_id = guild_id.into().0;
+
+        let (position, start, end) = self.seekable(guild_id)?.ok_or(AndelinkError::PositionUnknown)?;
+
+        let target = (position as i64).saturating_add(offset_ms).max(start as i64).min(end as i64);
+
+        self.send_seek(guild_id, target as u64)
     }
 
-    async fn seek(&mut self, guild_id: impl Into<DiscordGuildId>, time: Duration) -> AndelinkResult<()> {
-        let socket = if let Some(x) = &mut self.socket_write { x } else {
-            return Err(AndelinkError::NoWebsocket);
-        };
+    fn send_seek(&self, guild_id: u64, position: u64) -> AndelinkResult<()> {
+        // The fade out is scheduled again from the new position, starting from the restored volume
+        self.reset_fade_out(guild_id)?;
 
         let payload = crate::model::events::Seek {
-            position: time.as_millis() as u64,
+            position,
         };
 
-        crate::model::Codes::Seek(payload).send(guild_id, socket).await?;
+        self.send(guild_id, Codes::Seek(payload))?;
+
+        // Keep the position until the server reports the new one
+        if let Some(mut player) = self.players.get_mut(&guild_id) {
+            if let Some(info) = player.now_playing.as_mut().and_then(|current| current.track.info.as_mut()) {
+                info.position = position;
+            }
+        }
+
+        self.schedule_fade_out(guild_id);
 
         Ok(())
     }
 
-    async fn volume(&mut self, guild_id: impl Into<DiscordGuildId>, volume: u16) -> AndelinkResult<()> {
+    fn volume(&self, guild_id: impl Into<DiscordGuildId>, volume: u16) -> AndelinkResult<()> {
         use std::cmp::{max, min};
 
-        let socket = if let Some(x) = &mut self.socket_write { x } else {
-            return Err(AndelinkError::NoWebsocket);
-        };
-
+        let guild_id = guild_id.into();
         let good_volume = max(min(volume, 1000), 0);
 
-        let payload = crate::model::events::Volume {
-            volume: good_volume,
-        };
+        // An explicit volume wins over any fade
+        self.cancel_fade(guild_id.0);
+
+        self.send_volume(guild_id.0, good_volume)?;
 
-        crate::model::Codes::Volume(payload).send(guild_id, socket).await?;
+        if let Some(mut player) = self.players.get_mut(&guild_id.0) {
+            player.fade_base = None;
+        }
 
         Ok(())
     }
 
-    async fn equalize_all(&mut self, guild_id: impl Into<DiscordGuildId>, bands: [f64; 15]) -> AndelinkResult<()> {
-        let socket = if let Some(x) = &mut self.socket_write { x } else {
-            return Err(AndelinkError::NoWebsocket);
-        };
+    fn equalize_all(&self, guild_id: impl Into<DiscordGuildId>, bands: [f64; 15]) -> AndelinkResult<()> {
+        let guild_id = guild_id.into();
+
+        let gains = bands;
 
         let bands = bands.iter().enumerate().map(|(index, i)| {
             crate::model::events::Band {
@@ -259,29 +952,38 @@ impl NodeInner {
             bands,
         };
 
-        crate::model::Codes::Equalize(payload).send(guild_id, socket).await?;
+        self.send(guild_id, Codes::Equalize(payload))?;
+
+        if let Some(mut player) = self.players.get_mut(&guild_id.0) {
+            player.equalizer = gains;
+            player.preset = None;
+        }
 
         Ok(())
     }
 
-    async fn equalize_band(&mut self, guild_id: impl Into<DiscordGuildId>, band: crate::model::events::Band) -> AndelinkResult<()> {
-        let socket = if let Some(x) = &mut self.socket_write { x } else {
-            return Err(AndelinkError::NoWebsocket);
-        };
+    fn equalize_band(&self, guild_id: impl Into<DiscordGuildId>, band: crate::model::events::Band) -> AndelinkResult<()> {
+        let guild_id = guild_id.into();
 
         let payload = crate::model::events::Equalize {
-            bands: vec![band],
+            bands: vec![band.clone()],
         };
 
-        crate::model::Codes::Equalize(payload).send(guild_id, socket).await?;
+        self.send(guild_id, Codes::Equalize(payload))?;
+
+        if let Some(mut player) = self.players.get_mut(&guild_id.0) {
+            if let Some(gain) = player.equalizer.get_mut(band.band as usize) {
+                *gain = band.gain;
+            }
+
+            player.preset = None;
+        }
 
         Ok(())
     }
 
-    async fn equalize_reset(&mut self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<()> {
-        let socket = if let Some(x) = &mut self.socket_write { x } else {
-            return Err(AndelinkError::NoWebsocket);
-        };
+    fn equalize_reset(&self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<()> {
+        let guild_id = guild_id.into();
 
         let bands = (0..=14).map(|i| {
             crate::model::events::Band {
@@ -294,7 +996,12 @@ impl NodeInner {
             bands,
         };
 
-        crate::model::Codes::Equalize(payload).send(guild_id, socket).await?;
+        self.send(guild_id, Codes::Equalize(payload))?;
+
+        if let Some(mut player) = self.players.get_mut(&guild_id.0) {
+            player.equalizer = [0.; 15];
+            player.preset = None;
+        }
 
         Ok(())
     }
@@ -307,53 +1014,200 @@ impl UniversalNode {
         let rest = inner.rest.clone();
 
         Arc::new(Self {
-            inner: RwLock::new(inner),
+            inner,
             http: http_client,
-            rest_url: rest
+            rest_url: rest,
+            shutting_down: AtomicBool::new(false),
+            shutdown_notify: Notify::new(),
+            task: Mutex::new(None)
         })
     }
 
+    /// Returns whether the node is being removed from cluster and won't reconnect anymore.
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    /// Marks the node as being removed, so it's not added back to cluster when it reconnects.
+    pub(crate) fn mark_shutting_down(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    /// Closes the connection with a proper close frame, stops the node task and waits for it to finish.
+    pub(crate) async fn shutdown(&self) {
+        self.mark_shutting_down();
+
+        if self.is_connected() {
+            self.close();
+        }
+
+        self.shutdown_notify.notify_one();
+
+        let handle = self.task.lock().take();
+
+        if let Some(mut handle) = handle {
+            if tokio::time::timeout(Duration::from_secs(5), &mut handle).await.is_err() {
+                warn!("Node id {} task didn't stop in time, aborting it", self.node_id);
+
+                handle.abort();
+            }
+        }
+    }
+
+    /// Writes every message received through the channel into the websocket.
+    /// Messages that are already waiting are written together before flushing the socket.
+    async fn write_loop(node_id: u8, mut socket: WebSocketConnection, mut receiver: UnboundedReceiver<TungsteniteMessage>) {
+        while let Some(message) = receiver.recv().await {
+            let mut result = socket.feed(message).await;
+
+            while result.is_ok() {
+                match receiver.try_recv() {
+                    Ok(message) => result = socket.feed(message).await,
+                    Err(_) => break
+                }
+            }
+
+            if result.is_ok() {
+                result = socket.flush().await;
+            }
+
+            if let Err(why) = result {
+                error!("Node id {} failed to send payload, error: {}", node_id, why.to_string());
+                break;
+            }
+        }
+    }
+
     pub fn run(node: Arc<Self>) {
-        use crate::events::{process, EventType};
 
-        tokio::spawn(async move {
-            let (node_id, cluster) = {
-                let read = node.read().await;
+        let task_node = Arc::clone(&node);
 
-                (read.node_id, Arc::clone(&read.cluster))
-            };
+        let handle = tokio::spawn(async move {
+            let node = task_node;
+
+            let node_id = node.node_id;
+            let cluster = Arc::clone(&node.cluster);
+            let policy = node.reconnect_policy.clone().unwrap_or_else(|| cluster.reconnect_policy.clone());
 
-            let mut actual_reconnection_attempt = 1u8;
-            let max_reconnect_attempts = cluster.reconnect_attempts;
+            let max_reconnect_attempts = policy.max_attempts.map(|max| max.to_string()).unwrap_or_else(|| "unlimited".to_string());
+            let mut actual_reconnection_attempt = 1u32;
+            let mut connected_once = false;
 
-            while !(actual_reconnection_attempt > max_reconnect_attempts) {
+            while policy.should_retry(actual_reconnection_attempt) && !node.is_shutting_down() {
                 info!("Node id {} trying to connect to server, attempt {}", node_id, actual_reconnection_attempt);
 
-                let url = node.read().await.get_ws_request();
+                if connected_once || actual_reconnection_attempt > 1 {
+                    process(Arc::clone(&node), Arc::clone(&cluster.event_handler), EventType::NodeReconnecting(NodeReconnecting { attempt: actual_reconnection_attempt }));
+                }
+
+                let url = node.get_ws_request();
 
                 let stream = tokio_tungstenite::connect_async(url).await;
 
                 if let Err(_) = stream {
-                    actual_reconnection_attempt += 1;
+                    let delay = policy.delay_for(actual_reconnection_attempt);
 
-                    warn!("Node id {} failed to reconnect to server (attempt {}/{}), waiting 5s before reconnecting", node_id, actual_reconnection_attempt - 1, max_reconnect_attempts);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                    warn!("Node id {} failed to reconnect to server (attempt {}/{})", node_id, actual_reconnection_attempt, max_reconnect_attempts);
+
+                    actual_reconnection_attempt = actual_reconnection_attempt.saturating_add(1);
+
+                    if policy.should_retry(actual_reconnection_attempt) {
+                        info!("Node id {} waiting {:?} before reconnecting", node_id, delay);
+
+                        tokio::select! {
+                            _ = tokio::time::sleep(delay) => (),
+                            _ = node.shutdown_notify.notified() => ()
+                        }
+                    }
                 } else {
-                    let (write, mut read) = stream.unwrap().0.split();
+                    let (stream, response) = stream.unwrap();
+
+                    let resumed = response.headers()
+                        .get("Session-Resumed")
+                        .map(|value| value == "true")
+                        .unwrap_or(false);
+
+                    let (write, mut read) = stream.split();
 
                     info!("Node id {} connected successfully to server", node_id);
 
                     actual_reconnection_attempt = 1;
+                    connected_once = true;
 
-                    Self::add_to_cluster(&cluster, node_id, Arc::clone(&node));
+                    let (sender, receiver) = mpsc::unbounded_channel();
+
+                    let writer = tokio::spawn(Self::write_loop(node_id, write, receiver));
 
                     {
-                        let mut node_write = node.write().await;
+                        let mut writer_lock = node.writer.lock();
+
+                        // Buffered payloads are only meaningful if the players they belong to still exist
+                        if resumed {
+                            node.flush_outbox(&sender);
+                        } else {
+                            node.clear_outbox();
+                        }
+
+                        *writer_lock = Some(sender);
+
+                        node.reconnecting.store(false, Ordering::SeqCst);
+                    }
+
+                    if resumed {
+                        info!("Node id {} resumed its previous session, keeping {} players", node_id, node.players.len());
+                    } else if !node.players.is_empty() {
+                        // The server destroyed every player of the previous session, recreate them from the local state
+                        warn!("Node id {} couldn't resume its previous session, recreating {} players", node_id, node.players.len());
+
+                        let guilds = node.players.iter().map(|item| *item.key()).collect::<Vec<_>>();
+
+                        for guild_id in guilds {
+                            let player = match node.players.remove(&guild_id) {
+                                Some((_, player)) => player,
+                                None => continue
+                            };
+
+                            if let Err(why) = node.restore_player(guild_id, player) {
+                                error!("Node id {} failed to recreate player of guild id {}, error: {}", node_id, guild_id, why.to_string());
+
+                                let event = PlayerDropped {
+                                    guild_id,
+                                    reason: why.to_string(),
+                                };
+
+                                process(Arc::clone(&node), Arc::clone(&cluster.event_handler), EventType::PlayerDropped(event));
+                            }
+                        }
+                    }
 
-                        node_write.socket_write = Some(write);
+                    if let Err(why) = node.configure_resuming() {
+                        error!("Node id {} failed to configure resuming, error: {}", node_id, why.to_string());
                     }
 
-                    while let Some(Ok(msg)) = read.next().await {
+                    Self::add_to_cluster(&cluster, node_id, Arc::clone(&node));
+
+                    process(Arc::clone(&node), Arc::clone(&cluster.event_handler), EventType::NodeConnected(NodeConnected { resumed }));
+
+                    let mut disconnection = NodeDisconnected {
+                        code: None,
+                        reason: None
+                    };
+
+                    loop {
+                        let msg = tokio::select! {
+                            msg = read.next() => msg,
+                            _ = node.shutdown_notify.notified() => None
+                        };
+
+                        let msg = match msg {
+                            Some(Ok(msg)) => msg,
+                            Some(Err(why)) => {
+                                disconnection.reason = Some(why.to_string());
+                                break;
+                            },
+                            None => break
+                        };
+
                         match msg {
                             TungsteniteMessage::Text(t) => {
                                 if let Ok(payload) = serde_json::from_str::<GatewayEvent>(&t) {
@@ -362,28 +1216,26 @@ impl UniversalNode {
                                             if let Ok(stats) = serde_json::from_str::<Stats>(&t) {
 
                                                 // Set last stats
-                                                node.write().await.stats = Some(stats.clone());
-                                                
+                                                *node.stats.write() = Some(stats.clone());
+
                                                 // Dispatch the event
                                                 process(Arc::clone(&node), Arc::clone(&cluster.event_handler), EventType::Stats(stats));
                                             }
                                         },
                                         "playerUpdate" => {
                                             if let Ok(player_update) = serde_json::from_str::<crate::model::gateway::PlayerUpdate>(&t) {
-                                                {
-                                                    let mut node_write = node.write().await;
+                                                if let Some(mut player) = node.players.get_mut(&player_update.guild_id) {
+                                                    if let Some(current_track) = player.now_playing.as_mut() {
+                                                        if let Some(mut info) = current_track.track.info.as_mut() {
 
-                                                    if let Some(player) = node_write.players.get_mut(&player_update.guild_id) {
-                                                        if let Some(current_track) = player.now_playing.as_mut() {
-                                                            if let Some(mut info) = current_track.track.info.as_mut() {
-
-                                                                // Set the new position provided by lavalink/andesite server
-                                                                info.position = player_update.state.position;
-                                                            }
+                                                            // Set the new position provided by lavalink/andesite server
+                                                            info.position = player_update.state.position;
                                                         }
                                                     }
                                                 }
 
+                                                node.schedule_fade_out(player_update.guild_id);
+
                                                 // Dispatch the event
                                                 process(Arc::clone(&node), Arc::clone(&cluster.event_handler), EventType::PlayerUpdate(player_update));
                                             }
@@ -392,7 +1244,7 @@ impl UniversalNode {
                                             match payload.event_type.unwrap().as_str() {
                                                 "TrackStartEvent" => {
                                                     if let Ok(track_start) = serde_json::from_str::<TrackStart>(&t) {
-                                                        
+
                                                         // Dispatch the event
                                                         process(Arc::clone(&node), Arc::clone(&cluster.event_handler), EventType::TrackStart(track_start));
                                                     }
@@ -400,38 +1252,62 @@ impl UniversalNode {
                                                 "TrackEndEvent" => {
                                                     if let Ok(track_end) = serde_json::from_str::<TrackFinish>(&t) {
 
-                                                        if track_end.reason == "FINISHED" {
-                                                            let mut should_play_next = false;
-
-                                                            let mut node_write = node.write().await;
-
-                                                            if let Some(player) = node_write.players.get_mut(&track_end.guild_id) {
-
-                                                                // Remove track from queue
-                                                                player.queue.remove(0);
-
-                                                                // Set now playing
-                                                                player.now_playing = None;
-
-                                                                // Check if we should play next track
-                                                                if player.queue.len() >= 1 { should_play_next = true; }
+                                                        // A track that failed to load won't ever finish, so treat it the same way
+                                                        if track_end.reason == "FINISHED" || track_end.reason == "LOAD_FAILED" {
+                                                            let finished = node.players.get(&track_end.guild_id)
+                                                                .and_then(|player| player.now_playing.clone());
+
+                                                            match node.advance_queue(track_end.guild_id, track_end.reason == "LOAD_FAILED") {
+                                                                Ok(false) if track_end.reason == "FINISHED" => {
+                                                                    if let Some(finished) = finished {
+                                                                        UniversalNode::autoplay(Arc::clone(&node), track_end.guild_id, finished);
+                                                                    }
+                                                                },
+                                                                Err(why) => error!("Error playing on guild id: {}, error: {}", track_end.guild_id, why.to_string()),
+                                                                _ => ()
                                                             }
+                                                        }
 
-                                                            if should_play_next {
-                                                                // Play next track
-                                                                if let Err(why) = node_write.play_next(track_end.guild_id).await {
-                                                                    error!("Error playing on guild id: {}, error: {}", track_end.guild_id, why.to_string())
-                                                                }
+                                                        // Dispatch the event
+                                                        process(Arc::clone(&node), Arc::clone(&cluster.event_handler), EventType::TrackFinish(track_end));
+                                                    }
+                                                },
+                                                "TrackStuckEvent" => {
+                                                    if let Ok(track_stuck) = serde_json::from_str::<TrackStuck>(&t) {
+                                                        let is_current = node.players.get(&track_stuck.guild_id)
+                                                            .and_then(|player| player.now_playing.as_ref().map(|current| current.track.track == track_stuck.track))
+                                                            .unwrap_or(false);
+
+                                                        // Stuck tracks never end by themselves, so move to the next one or stop the player.
+                                                        // The replaced/stopped track ends with a reason that doesn't advance the queue again
+                                                        if is_current {
+                                                            let result = match node.advance_queue(track_stuck.guild_id, true) {
+                                                                Ok(false) => node.inner.send_stop(track_stuck.guild_id),
+                                                                Ok(true) => Ok(()),
+                                                                Err(why) => Err(why)
+                                                            };
+
+                                                            if let Err(why) = result {
+                                                                error!("Error skipping stuck track on guild id: {}, error: {}", track_stuck.guild_id, why.to_string())
                                                             }
                                                         }
 
                                                         // Dispatch the event
-                                                        process(Arc::clone(&node), Arc::clone(&cluster.event_handler), EventType::TrackFinish(track_end));
+                                                        process(Arc::clone(&node), Arc::clone(&cluster.event_handler), EventType::TrackStuck(track_stuck));
+                                                    }
+                                                },
+                                                "TrackExceptionEvent" => {
+                                                    if let Ok(track_exception) = serde_json::from_str::<TrackException>(&t) {
+
+                                                        // The queue is advanced by the `LOAD_FAILED` track end event sent after this one
+
+                                                        // Dispatch the event
+                                                        process(Arc::clone(&node), Arc::clone(&cluster.event_handler), EventType::TrackException(track_exception));
                                                     }
                                                 },
                                                 "WebSocketClosedEvent" => {
                                                     if let Ok(socket_closed) = serde_json::from_str::<WebSocketClosed>(&t) {
-                                                        
+
                                                         // Distpatch the event
                                                         process(Arc::clone(&node), Arc::clone(&cluster.event_handler), EventType::WebSocketClosed(socket_closed));
                                                     }
@@ -443,23 +1319,100 @@ impl UniversalNode {
                                     }
                                 }
                             },
-                            TungsteniteMessage::Close(_) => break,
+                            TungsteniteMessage::Close(frame) => {
+                                if let Some(frame) = frame {
+                                    disconnection.code = Some(u16::from(frame.code));
+                                    disconnection.reason = Some(frame.reason.to_string());
+                                }
+
+                                break
+                            },
                             _ => ()
                         }
                     }
 
+                    // Dropping the sender stops the writer once it sent every pending payload
+                    {
+                        let mut writer_lock = node.writer.lock();
+
+                        writer_lock.take();
+
+                        // Start buffering payloads until the connection is back
+                        node.reconnecting.store(!node.is_shutting_down(), Ordering::SeqCst);
+                    }
+
+                    if let Err(why) = writer.await {
+                        error!("Node id {} writer task failed, error: {}", node_id, why.to_string());
+                    }
+
                     // Temporarily delete the node from cluster so we won't try to play anithing on it until reconnect
                     if cluster.nodes.contains_key(&node_id) {
                         info!("Temporarily removing node id {} from cluster due to disconnection", node_id);
 
                         Self::remove_from_cluster(&cluster, node_id);
                     }
+
+                    process(Arc::clone(&node), Arc::clone(&cluster.event_handler), EventType::NodeDisconnected(disconnection));
                 }
             }
 
+            node.reconnecting.store(false, Ordering::SeqCst);
+            node.clear_outbox();
+
+            // The cluster takes care of the players if the node was removed on purpose
+            if node.is_shutting_down() {
+                info!("Node id {} stopped", node_id);
+                return;
+            }
+
             // If node reaches max attempts, exit the task and remove it from cluster
             info!("Node id {} reached max connection attempts, removing from cluster and disconnecting", node_id);
             Self::remove_from_cluster(&cluster, node_id);
+            cluster.registry.remove(&node_id);
+
+            // Move the players to other nodes so they don't get lost
+            cluster.migrate_players(Arc::clone(&node)).await;
+
+            process(Arc::clone(&node), Arc::clone(&cluster.event_handler), EventType::NodeRemoved);
+        });
+
+        *node.task.lock() = Some(handle);
+    }
+
+    /// Asks the autoplay provider of the player for a track to play after the last one of the queue, if it has one.
+    fn autoplay(node: Arc<Self>, guild_id: u64, last: QueuedTrack) {
+        let provider = match node.players.get(&guild_id).and_then(|player| player.autoplay.clone()) {
+            Some(provider) => provider,
+            None => return
+        };
+
+        tokio::spawn(async move {
+            let channel = last.channel;
+
+            let track = match provider.next_track(Arc::clone(&node), last).await {
+                Some(track) => track,
+                None => return
+            };
+
+            let queued = QueuedTrack {
+                track,
+                requester: Some(TrackRequester::autoplay()),
+                channel,
+                ..Default::default()
+            };
+
+            // Something else could have been queued while the provider was running
+            let idle = node.players.get(&guild_id)
+                .map_or(false, |player| player.now_playing.is_none() && player.queue.is_empty());
+
+            if !idle {
+                return;
+            }
+
+            // Autoplay tracks follow the same limits and duplicate policy as any other track
+            if let Err(why) = node.queue_track(guild_id, queued, None) {
+                warn!("Couldn't queue autoplay track on guild id: {}, error: {}", guild_id, why.to_string());
+            }
         });
     }
 
@@ -470,7 +1423,18 @@ impl UniversalNode {
     }
 
     fn add_to_cluster(cluster: &Arc<Cluster>, id: u8, node: Arc<Self>) {
-        cluster.nodes.insert(id, node);
+        if node.is_shutting_down() {
+            info!("Node id {} is being removed, not adding it back to cluster", id);
+            return;
+        }
+
+        cluster.nodes.insert(id, Arc::clone(&node));
+
+        // The node may have been marked while inserting it, don't leave it behind
+        if node.is_shutting_down() {
+            cluster.nodes.remove(&id);
+            return;
+        }
 
         info!("Node id {} added to cluster successfully", id);
     }
@@ -498,9 +1462,7 @@ impl UniversalNode {
 
     /// Method to create a session and be able to connect the server to discord
     pub async fn create_session(&self, guild_id: impl Into<DiscordGuildId>, conn_info: &ConnectionInfo) -> AndelinkResult<()> {
-        let mut node_write = self.inner.write().await;
-
-        node_write.create_session(guild_id, conn_info).await
+        self.inner.create_session(guild_id, conn_info)
     }
 
     /// Constructor for playing a track.
@@ -512,7 +1474,9 @@ impl UniversalNode {
     }
 
     /// Constructor shortcut to add entire playlists to queue, **map** will be called for every track converted to play parameters
-    pub fn play_playlist<'a, F>(&'a self, guild: impl Into<DiscordGuildId>, tracks: Vec<Track>, map: F) -> Vec<PlayParameters<'a>>
+    ///
+    /// Fails if the whole playlist can't be queued without breaking the queue limits of the guild.
+    pub fn play_playlist<'a, F>(&'a self, guild: impl Into<DiscordGuildId>, tracks: Vec<Track>, map: F) -> AndelinkResult<Vec<PlayParameters<'a>>>
     where
         F: for<'b> Fn(&'b mut PlayParameters<'a>) -> &'b mut PlayParameters<'a>
     {
@@ -527,38 +1491,42 @@ impl UniversalNode {
             playlist.push(p);
         }
 
-        playlist
+        match self.players.get(&guild.0) {
+            Some(player) => player.check_limits(playlist.iter().map(|p| (&p.track, p.requester.as_ref())))?,
+            None => return Err(AndelinkError::PlayerNotFound)
+        }
+
+        Ok(playlist)
     }
 
     /// Destroys the current player.
     /// When this is run, `create_session()` needs to be run again.
     pub async fn destroy(&self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<()> {
-        let mut node_write = self.inner.write().await;
-
-        node_write.destroy(guild_id).await
+        self.inner.destroy(guild_id)
     }
 
      /// Stops the current player.
      pub async fn stop(&self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<()> {
-         let mut node_write = self.inner.write().await;
-
-         node_write.stop(guild_id).await
+         self.inner.stop(guild_id)
      }
 
       /// Skips the current playing track to the next item on the queue.
     ///
     /// If nothing is in the queue, player will automatically be stopped
     pub async fn skip(&self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<Option<QueuedTrack>> {
-        let mut node_write = self.inner.write().await;
+        self.inner.skip(guild_id)
+    }
 
-        node_write.skip(guild_id).await
+    /// Plays again the last track of the history, the current track being played right after it.
+    ///
+    /// Returns the replayed track, or `None` if the history is empty.
+    pub async fn previous(&self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<Option<QueuedTrack>> {
+        self.inner.previous(guild_id)
     }
 
     /// Sets the pause status.
     pub async fn set_pause(&self, guild_id: impl Into<DiscordGuildId>, pause: bool) -> AndelinkResult<()> {
-        let mut node_write = self.inner.write().await;
-
-        node_write.set_pause(guild_id, pause).await
+        self.inner.set_pause(guild_id, pause)
     }
 
     /// Sets pause status to `True`
@@ -571,18 +1539,44 @@ impl UniversalNode {
         self.set_pause(guild_id, false).await
     }
 
-    /// Jumps to a specific time in the currently playing track.
+    /// Jumps to a specific time in the currently playing track, times past its `end_time` or length are clamped to it.
+    ///
+    /// Fails if nothing is playing or the track can't be seeked, like streams.
     pub async fn seek(&self, guild_id: impl Into<DiscordGuildId>, time: Duration) -> AndelinkResult<()> {
-        let mut node_write = self.inner.write().await;
+        self.inner.seek(guild_id, time)
+    }
+
+    /// Moves the currently playing track by the given milliseconds from the last position reported by the server,
+    /// forward if positive and backward if negative. The offset is signed because a `Duration` can't be negative,
+    /// see [`UniversalNode::rewind`] and [`UniversalNode::fast_forward`] to use one.
+    ///
+    /// The result is clamped to the part of the track that is played, from its `start_time` to its `end_time` or length.
+    ///
+    /// Fails if nothing is playing, the track can't be seeked, like streams, or its position is unknown.
+    pub async fn seek_by(&self, guild_id: impl Into<DiscordGuildId>, offset_ms: i64) -> AndelinkResult<()> {
+        self.inner.seek_by(guild_id, offset_ms)
+    }
+
+    /// Moves the currently playing track backward, see [`UniversalNode::seek_by`].
+    pub async fn rewind(&self, guild_id: impl Into<DiscordGuildId>, time: Duration) -> AndelinkResult<()> {
+        self.inner.seek_by(guild_id, -(time.as_millis().min(i64::MAX as u128) as i64))
+    }
 
-        node_write.seek(guild_id, time).await
+    /// Moves the currently playing track forward, see [`UniversalNode::seek_by`].
+    pub async fn fast_forward(&self, guild_id: impl Into<DiscordGuildId>, time: Duration) -> AndelinkResult<()> {
+        self.inner.seek_by(guild_id, time.as_millis().min(i64::MAX as u128) as i64)
     }
 
-    /// Sets the volume of the player.
+    /// Sets the volume of the player, stopping any fade.
     pub async fn volume(&self, guild_id: impl Into<DiscordGuildId>, volume: u16) -> AndelinkResult<()> {
-        let mut node_write = self.inner.write().await;
+        self.inner.volume(guild_id, volume)
+    }
 
-        node_write.volume(guild_id, volume).await
+    /// Changes the volume of the player to the given one step by step over the given time, in the background.
+    ///
+    /// The fade is stopped by any other volume command.
+    pub async fn fade_volume(&self, guild_id: impl Into<DiscordGuildId>, volume: u16, duration: Duration) -> AndelinkResult<()> {
+        self.inner.fade_volume(guild_id.into().0, volume, duration)
     }
 
     /// Sets all equalizer levels.
@@ -592,23 +1586,189 @@ impl UniversalNode {
     /// Valid values range from -0.25 to 1.0, where -0.25 means the given band is completely muted, and 0.25 means it is doubled.
     /// Modifying the gain could also change the volume of the output.
     pub async fn equalize_all(&self, guild_id: impl Into<DiscordGuildId>, bands: [f64; 15]) -> AndelinkResult<()> {
-        let mut node_write = self.inner.write().await;
-
-        node_write.equalize_all(guild_id, bands).await
+        self.inner.equalize_all(guild_id, bands)
     }
 
     /// Equalizes a specific band.
     pub async fn equalize_band(&self, guild_id: impl Into<DiscordGuildId>, band: crate::model::events::Band) -> AndelinkResult<()> {
-        let mut node_write = self.inner.write().await;
-
-        node_write.equalize_band(guild_id, band).await
+        self.inner.equalize_band(guild_id, band)
     }
 
     /// Resets all equalizer levels.
     pub async fn equalize_reset(&self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<()> {
-        let mut node_write = self.inner.write().await;
+        self.inner.equalize_reset(guild_id)
+    }
+
+    /// Returns a snapshot of the current state of the player.
+    pub fn player(&self, guild_id: impl Into<DiscordGuildId>) -> Option<Player> {
+        self.players.get(&guild_id.into().0).map(|player| player.value().clone())
+    }
+
+    /// Sets how the queue of the player advances when a track finishes.
+    pub fn set_loop_mode(&self, guild_id: impl Into<DiscordGuildId>, mode: LoopMode) -> AndelinkResult<()> {
+        self.with_player(guild_id.into().0, |player| {
+            player.loop_mode = mode;
+            Ok(())
+        })
+    }
+
+    /// Removes the upcoming track at the given position, 0 being the next track.
+    pub fn remove_track(&self, guild_id: impl Into<DiscordGuildId>, index: usize) -> AndelinkResult<QueuedTrack> {
+        self.with_player(guild_id.into().0, |player| player.remove_upcoming(index))
+    }
+
+    /// Removes the upcoming tracks in the given range of positions, 0 being the next track.
+    pub fn remove_tracks(&self, guild_id: impl Into<DiscordGuildId>, range: impl RangeBounds<usize>) -> AndelinkResult<Vec<QueuedTrack>> {
+        self.with_player(guild_id.into().0, |player| player.remove_upcoming_range(range))
+    }
+
+    /// Moves an upcoming track from one position to another, 0 being the next track.
+    pub fn move_track(&self, guild_id: impl Into<DiscordGuildId>, from: usize, to: usize) -> AndelinkResult<()> {
+        self.with_player(guild_id.into().0, |player| player.move_upcoming(from, to))
+    }
+
+    /// Inserts a track at the given position of the upcoming tracks, 0 being the next track,
+    /// following the duplicate policy and the queue limits of the guild.
+    ///
+    /// If nothing is playing, the track starts playing.
+    pub fn insert_track(&self, guild_id: impl Into<DiscordGuildId>, index: usize, track: QueuedTrack) -> AndelinkResult<QueueOutcome> {
+        self.inner.queue_track(guild_id.into().0, track, Some(index))
+    }
+
+    /// Removes every upcoming track, keeping the one currently playing.
+    pub fn clear_queue(&self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<Vec<QueuedTrack>> {
+        self.with_player(guild_id.into().0, |player| Ok(player.clear_upcoming()))
+    }
+
+    /// Shuffles the upcoming tracks.
+    pub fn shuffle_queue(&self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<()> {
+        self.with_player(guild_id.into().0, |player| {
+            player.shuffle_upcoming();
+            Ok(())
+        })
+    }
+
+    /// Reverses the order of the upcoming tracks.
+    pub fn reverse_queue(&self, guild_id: impl Into<DiscordGuildId>) -> AndelinkResult<()> {
+        self.with_player(guild_id.into().0, |player| {
+            player.reverse_upcoming();
+            Ok(())
+        })
+    }
+
+    /// Returns the recently played tracks of the player, the most recent one first.
+    pub fn history(&self, guild_id: impl Into<DiscordGuildId>) -> Option<Vec<QueuedTrack>> {
+        self.players.get(&guild_id.into().0)
+            .map(|player| player.history.iter().rev().cloned().collect())
+    }
+
+    /// Sets how many played tracks the player keeps on its history, 0 disables it.
+    pub fn set_history_size(&self, guild_id: impl Into<DiscordGuildId>, size: usize) -> AndelinkResult<()> {
+        self.with_player(guild_id.into().0, |player| {
+            player.set_history_size(size);
+            Ok(())
+        })
+    }
+
+    /// Enables or disables the fair queue, which interleaves the tracks of different requesters
+    /// so a single user can't flood the queue. Tracks already queued are not reordered.
+    pub fn set_fair_queue(&self, guild_id: impl Into<DiscordGuildId>, enabled: bool) -> AndelinkResult<()> {
+        self.with_player(guild_id.into().0, |player| {
+            player.fair_queue = enabled;
+            Ok(())
+        })
+    }
+
+    /// Returns whether the fair queue is enabled for the player.
+    pub fn fair_queue(&self, guild_id: impl Into<DiscordGuildId>) -> Option<bool> {
+        self.players.get(&guild_id.into().0).map(|player| player.fair_queue)
+    }
+
+    /// Sets the limits checked before adding tracks to the queue of the player.
+    pub fn set_queue_limits(&self, guild_id: impl Into<DiscordGuildId>, limits: QueueLimits) -> AndelinkResult<()> {
+        self.with_player(guild_id.into().0, |player| {
+            player.limits = limits;
+            Ok(())
+        })
+    }
+
+    /// Returns the queue limits of the player.
+    pub fn queue_limits(&self, guild_id: impl Into<DiscordGuildId>) -> Option<QueueLimits> {
+        self.players.get(&guild_id.into().0).map(|player| player.limits.clone())
+    }
+
+    /// Sets what happens when a track that is already playing or queued is queued again.
+    pub fn set_duplicate_policy(&self, guild_id: impl Into<DiscordGuildId>, policy: DuplicatePolicy) -> AndelinkResult<()> {
+        self.with_player(guild_id.into().0, |player| {
+            player.duplicate_policy = policy;
+            Ok(())
+        })
+    }
+
+    /// Returns the duplicate policy of the player.
+    pub fn duplicate_policy(&self, guild_id: impl Into<DiscordGuildId>) -> Option<DuplicatePolicy> {
+        self.players.get(&guild_id.into().0).map(|player| player.duplicate_policy)
+    }
+
+    /// Sets the provider asked for a new track when the queue of the player runs dry, `None` disables autoplay.
+    pub fn set_autoplay(&self, guild_id: impl Into<DiscordGuildId>, provider: Option<Arc<dyn AutoplayProvider>>) -> AndelinkResult<()> {
+        self.with_player(guild_id.into().0, |player| {
+            player.autoplay = provider;
+            Ok(())
+        })
+    }
+
+    /// Changes the audio filters of the player, the given ones are merged with the filters it already has.
+    ///
+    /// ```ignore
+    /// node.filters(guild_id)
+    ///     .timescale(Timescale { speed: 1.25, ..Default::default() })
+    ///     .low_pass(None)
+    ///     .apply()
+    ///     .await?;
+    /// ```
+    pub fn filters(&self, guild_id: impl Into<DiscordGuildId>) -> FiltersBuilder<'_> {
+        FiltersBuilder::new(self, guild_id.into().0)
+    }
+
+    /// Applies a preset registered on the cluster, replacing every filter and the equalizer of the player.
+    pub async fn apply_preset(&self, guild_id: impl Into<DiscordGuildId>, name: &str) -> AndelinkResult<()> {
+        let preset = match self.cluster.presets.get(name) {
+            Some(preset) => preset.clone(),
+            None => return Err(AndelinkError::PresetNotFound(name.to_string()))
+        };
+
+        self.filters(guild_id).preset(name, preset).apply().await
+    }
+
+    /// Returns the name of the preset the filters of the player come from, if any.
+    pub fn active_preset(&self, guild_id: impl Into<DiscordGuildId>) -> Option<String> {
+        self.players.get(&guild_id.into().0).and_then(|player| player.preset.clone())
+    }
+
+    /// Returns whether the player is paused.
+    pub fn is_paused(&self, guild_id: impl Into<DiscordGuildId>) -> Option<bool> {
+        self.players.get(&guild_id.into().0).map(|player| player.paused)
+    }
+
+    /// Returns the volume of the player.
+    pub fn current_volume(&self, guild_id: impl Into<DiscordGuildId>) -> Option<u16> {
+        self.players.get(&guild_id.into().0).map(|player| player.volume)
+    }
+
+    /// Returns the gains of the 15 equalizer bands of the player.
+    pub fn equalizer(&self, guild_id: impl Into<DiscordGuildId>) -> Option<[f64; 15]> {
+        self.players.get(&guild_id.into().0).map(|player| player.equalizer)
+    }
+
+    /// Returns the audio filters of the player, the equalizer is returned by [`UniversalNode::equalizer`].
+    pub fn current_filters(&self, guild_id: impl Into<DiscordGuildId>) -> Option<Filters> {
+        self.players.get(&guild_id.into().0).map(|player| player.filters.clone())
+    }
 
-        node_write.equalize_reset(guild_id).await
+    /// Returns the loop mode of the player.
+    pub fn loop_mode(&self, guild_id: impl Into<DiscordGuildId>) -> Option<LoopMode> {
+        self.players.get(&guild_id.into().0).map(|player| player.loop_mode)
     }
 
-}
\ No newline at end of file
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use crate::model::events::{Filters, Band, Timescale, Rotation, LowPass, Karaoke};
+
+/// Named sets of filters that can be applied to a player with [`UniversalNode::apply_preset`].
+///
+/// Comes with the following presets: `bass_boost_low`, `bass_boost_medium`, `bass_boost_high`,
+/// `treble_boost`, `nightcore`, `vaporwave`, `8d`, `soft` and `karaoke`.
+///
+/// [`UniversalNode::apply_preset`]: crate::node::UniversalNode::apply_preset
+#[derive(Debug, Clone)]
+pub struct PresetRegistry {
+    presets: HashMap<String, Filters>
+}
+
+impl Default for PresetRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            presets: HashMap::new()
+        };
+
+        registry.register("bass_boost_low", equalizer(&[0.1, 0.08, 0.06, 0.02]));
+        registry.register("bass_boost_medium", equalizer(&[0.2, 0.15, 0.1, 0.05]));
+        registry.register("bass_boost_high", equalizer(&[0.3, 0.25, 0.2, 0.1, 0.05]));
+        registry.register("treble_boost", equalizer(&[0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.1, 0.15, 0.2, 0.25, 0.25]));
+
+        registry.register("nightcore", Filters {
+            timescale: Some(Timescale { speed: 1.25, pitch: 1.25, rate: 1. }),
+            ..Default::default()
+        });
+
+        registry.register("vaporwave", Filters {
+            timescale: Some(Timescale { speed: 0.85, pitch: 0.8, rate: 1. }),
+            ..Default::default()
+        });
+
+        registry.register("8d", Filters {
+            rotation: Some(Rotation { rotation_hz: 0.2 }),
+            ..Default::default()
+        });
+
+        registry.register("soft", Filters {
+            low_pass: Some(LowPass { smoothing: 20. }),
+            ..Default::default()
+        });
+
+        registry.register("karaoke", Filters {
+            karaoke: Some(Karaoke::default()),
+            ..Default::default()
+        });
+
+        registry
+    }
+}
+
+impl PresetRegistry {
+    /// Creates a registry without any preset.
+    pub fn empty() -> Self {
+        Self {
+            presets: HashMap::new()
+        }
+    }
+
+    /// Registers a preset, replacing the one with the same name, if any. Names are case insensitive.
+    pub fn register(&mut self, name: impl ToString, filters: Filters) -> &mut Self {
+        self.presets.insert(name.to_string().to_lowercase(), filters);
+
+        self
+    }
+
+    /// Gets the filters of a preset.
+    pub fn get(&self, name: &str) -> Option<&Filters> {
+        self.presets.get(&name.to_lowercase())
+    }
+
+    /// Returns the names of every registered preset, sorted alphabetically.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = self.presets.keys().map(|name| name.as_str()).collect::<Vec<_>>();
+
+        names.sort_unstable();
+
+        names
+    }
+}
+
+/// Filters with only the given equalizer gains, starting from the first band.
+fn equalizer(gains: &[f64]) -> Filters {
+    let bands = gains.iter().enumerate().map(|(index, gain)| {
+        Band {
+            band: index as u8,
+            gain: *gain,
+        }
+    }).collect();
+
+    Filters {
+        equalizer: Some(bands),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_presets_are_valid() {
+        let registry = PresetRegistry::default();
+
+        for name in registry.names() {
+            assert!(registry.get(name).unwrap().validate().is_ok(), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::time::Duration;
+use rand::Rng;
+
+/// Policy used by nodes to reconnect to the server after losing their connection.
+///
+/// The delay between attempts grows exponentially from `initial_delay` by `multiplier` up to `max_delay`,
+/// and is randomized by `jitter` so nodes don't reconnect all at once after a server restart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    /// Delay before the second attempt
+    pub initial_delay: Duration,
+    /// Maximum delay between attempts
+    pub max_delay: Duration,
+    /// Factor the delay is multiplied by after every failed attempt
+    pub multiplier: f64,
+    /// Fraction of the delay that is randomly added or subtracted, from 0 to 1
+    pub jitter: f64,
+    /// Maximum number of consecutive failed attempts, `None` retries forever
+    pub max_attempts: Option<u32>
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            multiplier: 2.,
+            jitter: 0.2,
+            max_attempts: Some(5)
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Creates a policy that never gives up reconnecting.
+    pub fn infinite() -> Self {
+        Self {
+            max_attempts: None,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the delay before the second attempt.
+    pub fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Sets the maximum delay between attempts.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Sets the factor the delay is multiplied by after every failed attempt.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.);
+        self
+    }
+
+    /// Sets the fraction of the delay that is randomly added or subtracted, clamped from 0 to 1.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.max(0.).min(1.);
+        self
+    }
+
+    /// Sets the maximum number of consecutive failed attempts before the node is removed from cluster.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts);
+        self
+    }
+
+    /// Makes the node retry forever.
+    pub fn unlimited(mut self) -> Self {
+        self.max_attempts = None;
+        self
+    }
+
+    /// Returns whether the given attempt, starting from 1, should be made.
+    pub fn should_retry(&self, attempt: u32) -> bool {
+        match self.max_attempts {
+            Some(max) => attempt <= max,
+            None => true
+        }
+    }
+
+    /// Returns the time to wait after the given failed attempt, starting from 1.
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+
+        let delay = (self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent))
+            .min(self.max_delay.as_secs_f64());
+
+        let jitter = if self.jitter > 0. {
+            rand::thread_rng().gen_range(-self.jitter..=self.jitter)
+        } else {
+            0.
+        };
+
+        Duration::from_secs_f64((delay * (1. + jitter)).max(0.))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> ReconnectPolicy {
+        ReconnectPolicy::default()
+            .initial_delay(Duration::from_secs(1))
+            .max_delay(Duration::from_secs(10))
+            .multiplier(2.)
+            .jitter(0.)
+    }
+
+    #[test]
+    fn delay_grows_up_to_max() {
+        let policy = policy();
+
+        assert_eq!(policy.delay_for(1), Duration::from_secs(1));
+        assert_eq!(policy.delay_for(2), Duration::from_secs(2));
+        assert_eq!(policy.delay_for(3), Duration::from_secs(4));
+        assert_eq!(policy.delay_for(4), Duration::from_secs(8));
+        assert_eq!(policy.delay_for(5), Duration::from_secs(10));
+        assert_eq!(policy.delay_for(u32::MAX), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn first_attempt_uses_initial_delay() {
+        assert_eq!(policy().delay_for(0), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn jitter_stays_in_range() {
+        let policy = policy().jitter(0.2);
+
+        for _ in 0..100 {
+            let delay = policy.delay_for(3);
+
+            assert!(delay >= Duration::from_millis(3199) && delay <= Duration::from_millis(4801), "{:?}", delay);
+        }
+    }
+
+    #[test]
+    fn setters_clamp_values() {
+        let policy = ReconnectPolicy::default().multiplier(0.5).jitter(3.);
+
+        assert!(policy.multiplier >= 1.);
+        assert!(policy.jitter <= 1.);
+    }
+
+    #[test]
+    fn retries_until_max_attempts() {
+        let policy = ReconnectPolicy::default().max_attempts(3);
+
+        assert!(policy.should_retry(3));
+        assert!(!policy.should_retry(4));
+        assert!(ReconnectPolicy::infinite().should_retry(u32::MAX));
+    }
+}
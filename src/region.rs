@@ -0,0 +1,100 @@
+//! Mapping of Discord voice endpoints to regions, used to prefer nodes close to the voice server.
+
+/// Extracts the voice server name from a Discord voice endpoint,
+/// for example `rotterdam1234.discord.media:443` gives `rotterdam`
+/// and `c-ams10-1a2b3c4d.discord.media` gives `ams`.
+pub fn server_name(endpoint: &str) -> Option<String> {
+    let endpoint = endpoint.trim_start_matches("wss://");
+    let host = endpoint.split(['.', ':']).next()?;
+
+    // Newer endpoints are named `c-<code><number>-<hash>`
+    let host = match host.strip_prefix("c-") {
+        Some(rest) => rest.split('-').next()?,
+        None => host
+    };
+
+    let name = host.trim_end_matches(|c: char| c.is_ascii_digit()).trim_end_matches('-');
+
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_lowercase())
+    }
+}
+
+/// Maps a Discord voice server name, or the airport code of newer endpoints, to the region it's located on.
+pub fn region_of(server_name: &str) -> Option<&'static str> {
+    let region = match server_name {
+        "us-east" | "us-west" | "us-central" | "us-south" | "atlanta" | "newark" | "santa-clara"
+            | "seattle" | "dallas" | "chicago" | "miami" | "montreal" | "toronto"
+            | "atl" | "ewr" | "iad" | "sjc" | "lax" | "sea" | "dfw" | "ord" | "mia" | "yul" | "yyz" => "us",
+        "brazil" | "santiago" | "buenos-aires" | "gru" | "scl" | "eze" => "south-america",
+        "europe" | "eu-central" | "eu-west" | "rotterdam" | "amsterdam" | "frankfurt" | "london"
+            | "madrid" | "milan" | "stockholm" | "bucharest" | "finland" | "russia"
+            | "rtm" | "ams" | "fra" | "lhr" | "mad" | "mxp" | "arn" | "otp" | "hel" | "waw" | "cdg" => "europe",
+        "india" | "japan" | "hongkong" | "singapore" | "south-korea" | "dubai"
+            | "bom" | "nrt" | "hkg" | "sin" | "icn" | "dxb" | "tlv" => "asia",
+        "sydney" | "syd" => "oceania",
+        "southafrica" | "jnb" => "africa",
+        _ => return None
+    };
+
+    Some(region)
+}
+
+/// Returns the names a node region tag can match for the given Discord voice endpoint,
+/// that is, the voice server name and the region it's located on.
+pub fn endpoint_regions(endpoint: &str) -> Vec<String> {
+    let mut regions = Vec::new();
+
+    if let Some(name) = server_name(endpoint) {
+        if let Some(region) = region_of(&name) {
+            regions.push(region.to_string());
+        }
+
+        regions.push(name);
+    }
+
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_endpoints() {
+        assert_eq!(server_name("rotterdam1234.discord.media:443").as_deref(), Some("rotterdam"));
+        assert_eq!(server_name("us-east123.discord.media").as_deref(), Some("us-east"));
+        assert_eq!(server_name("santa-clara456.discord.media:443").as_deref(), Some("santa-clara"));
+        assert_eq!(server_name("wss://Rotterdam1234.discord.media:443").as_deref(), Some("rotterdam"));
+    }
+
+    #[test]
+    fn airport_code_endpoints() {
+        assert_eq!(server_name("c-ams10-1a2b3c4d.discord.media").as_deref(), Some("ams"));
+        assert_eq!(server_name("wss://c-ams10-1a2b3c4d.discord.media:443").as_deref(), Some("ams"));
+    }
+
+    #[test]
+    fn regions() {
+        assert_eq!(region_of("rotterdam"), Some("europe"));
+        assert_eq!(region_of("ams"), Some("europe"));
+        assert_eq!(region_of("us-east"), Some("us"));
+        assert_eq!(region_of("santa-clara"), Some("us"));
+        assert_eq!(region_of("foo"), None);
+
+        assert_eq!(endpoint_regions("c-ams10-1a2b3c4d.discord.media"), vec!["europe", "ams"]);
+        assert_eq!(endpoint_regions("santa-clara456.discord.media:443"), vec!["us", "santa-clara"]);
+    }
+
+    #[test]
+    fn unknown_hosts() {
+        assert_eq!(server_name("foo.example.com").as_deref(), Some("foo"));
+        assert_eq!(endpoint_regions("foo.example.com"), vec!["foo"]);
+
+        assert_eq!(server_name(""), None);
+        assert_eq!(server_name("1234.discord.media"), None);
+        assert!(endpoint_regions("").is_empty());
+    }
+}
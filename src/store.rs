@@ -0,0 +1,126 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serenity::model::id::GuildId as DiscordGuildId;
+use std::{
+    io::ErrorKind,
+    path::PathBuf
+};
+use crate::{
+    error::{AndelinkError, AndelinkResult},
+    model::{player::{Player, LoopMode}, track::QueuedTrack, events::Filters}
+};
+
+/// Persistent state of a player, enough to recreate its queue after a restart.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlayerSnapshot {
+    pub guild_id: u64,
+    pub now_playing: Option<QueuedTrack>,
+    /// Position of the current track in milliseconds
+    pub position: u64,
+    pub volume: u16,
+    /// Upcoming tracks, not including the current one
+    pub queue: Vec<QueuedTrack>,
+    pub loop_mode: LoopMode,
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    pub equalizer: [f64; 15],
+    #[serde(default)]
+    pub filters: Filters
+}
+
+impl PlayerSnapshot {
+    pub fn from_player(guild_id: u64, player: &Player) -> Self {
+        let position = player.now_playing.as_ref()
+            .and_then(|current| current.track.info.as_ref())
+            .map(|info| info.position)
+            .unwrap_or(0);
+
+        Self {
+            guild_id,
+            now_playing: player.now_playing.clone(),
+            position,
+            // Fades aren't saved, keep the volume the player had before fading
+            volume: player.fade_base.unwrap_or(player.volume),
+            queue: player.upcoming().to_vec(),
+            loop_mode: player.loop_mode,
+            paused: player.paused,
+            equalizer: player.equalizer,
+            filters: player.filters.clone()
+        }
+    }
+
+    /// Builds a player with the state of the snapshot, the current track being the first one of the queue.
+    pub(crate) fn into_player(self) -> Player {
+        let mut queue = self.queue;
+        let position = self.position;
+
+        let now_playing = self.now_playing.map(|mut current| {
+            if let Some(info) = current.track.info.as_mut() {
+                info.position = position;
+            }
+
+            queue.insert(0, current.clone());
+
+            current
+        });
+
+        Player {
+            guild: DiscordGuildId(self.guild_id),
+            now_playing,
+            volume: self.volume,
+            queue,
+            loop_mode: self.loop_mode,
+            paused: self.paused,
+            equalizer: self.equalizer,
+            filters: self.filters,
+            ..Default::default()
+        }
+    }
+}
+
+/// Storage used by the cluster to persist the players between restarts.
+#[async_trait]
+pub trait QueueStore: Send + Sync + 'static {
+    /// Replaces the stored snapshots with the given ones.
+    async fn save(&self, snapshots: Vec<PlayerSnapshot>) -> AndelinkResult<()>;
+    /// Returns the stored snapshots.
+    async fn load(&self) -> AndelinkResult<Vec<PlayerSnapshot>>;
+}
+
+/// Stores the snapshots as json on a file.
+#[derive(Debug, Clone)]
+pub struct JsonFileStore {
+    path: PathBuf
+}
+
+impl JsonFileStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into()
+        }
+    }
+}
+
+#[async_trait]
+impl QueueStore for JsonFileStore {
+    async fn save(&self, snapshots: Vec<PlayerSnapshot>) -> AndelinkResult<()> {
+        let json = serde_json::to_vec(&snapshots).map_err(|e| AndelinkError::Store(Box::new(e)))?;
+
+        // Write to a temporary file first so a crash never leaves a truncated store
+        let temp = self.path.with_extension("tmp");
+
+        tokio::fs::write(&temp, json).await.map_err(|e| AndelinkError::Store(Box::new(e)))?;
+        tokio::fs::rename(&temp, &self.path).await.map_err(|e| AndelinkError::Store(Box::new(e)))
+    }
+
+    async fn load(&self) -> AndelinkResult<Vec<PlayerSnapshot>> {
+        let json = match tokio::fs::read(&self.path).await {
+            Ok(json) => json,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(AndelinkError::Store(Box::new(e)))
+        };
+
+        serde_json::from_slice(&json).map_err(|e| AndelinkError::Store(Box::new(e)))
+    }
+}
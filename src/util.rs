@@ -70,7 +70,7 @@ pub async fn connect_to(ctx: &Context, guild: impl Into<GuildId>, channel: impl
                 let data = ctx.data.read().await;
                 data.get::<crate::cluster::Cluster>().expect("Unable to find andelink cluster").clone()
             };
-            let node = cluster.get_best().await?;
+            let node = cluster.get_best_for_connection(&conn_info).await?;
 
             node.create_session(&guild_id, &conn_info).await?;
         },